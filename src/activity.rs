@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    api::ActionV2,
+    db::Encode,
+    denomination::{Denomination, Denominator},
+    describe, params, positions, watchlists, Action, Actions,
+};
+
+describe::unit_enum! {
+    /// Operation performed by a transaction, inferred from the combination of
+    /// its actions.
+    #[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Operation {
+        SimpleDeposit,
+        Withdrawal,
+        Borrow,
+        Repayment,
+        /// Collateral supplied and borrowed against in the same transaction.
+        LeverageLoop,
+        /// Debt repaid and collateral withdrawn in the same transaction.
+        Deleverage,
+        /// Any other combination.
+        Rebalance,
+    }
+}
+
+fn classify(actions: &[&Actions]) -> Operation {
+    let has = |kind: Action, increase: bool| {
+        actions
+            .iter()
+            .any(|action| action.action == kind as u32 && (action.amount >= 0) == increase)
+    };
+    let supply = has(Action::Collateral, true);
+    let withdraw = has(Action::Collateral, false);
+    let borrow = has(Action::Borrow, true);
+    let repay = has(Action::Borrow, false);
+
+    match (supply, withdraw, borrow, repay) {
+        (true, false, false, false) => Operation::SimpleDeposit,
+        (false, true, false, false) => Operation::Withdrawal,
+        (false, false, true, false) => Operation::Borrow,
+        (false, false, false, true) => Operation::Repayment,
+        (true, false, true, false) => Operation::LeverageLoop,
+        (false, true, false, true) => Operation::Deleverage,
+        _ => Operation::Rebalance,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransactionActivityRequest {
+    address: Option<String>,
+    /// Scope the activity to the addresses of a watchlist.
+    watchlist: Option<u32>,
+    from_ledger: Option<u32>,
+    to_ledger: Option<u32>,
+    /// Maximum number of transactions to return, most recent first.
+    limit: Option<usize>,
+    denomination: Option<Denomination>,
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct TransactionActivity {
+        /// `None` for actions indexed before transaction hashes were tracked,
+        /// which are each reported on their own.
+        pub transaction: Option<String>,
+        pub operation: Operation,
+        pub timestamp: u64,
+        pub ledger: u32,
+        pub sources: Vec<String>,
+        pub actions: Vec<ActionV2>,
+    }
+}
+
+/// Groups actions by the transaction they belong to, most recent first.
+/// Actions without a transaction hash form a group of their own.
+fn group(actions: &[Actions]) -> Vec<(Option<&str>, Vec<&Actions>)> {
+    let mut grouped: BTreeMap<&str, Vec<&Actions>> = BTreeMap::new();
+    let mut unknown = Vec::new();
+    for action in actions {
+        match &action.transaction {
+            Some(transaction) => grouped.entry(transaction).or_default().push(action),
+            None => unknown.push((None, vec![action])),
+        }
+    }
+
+    let mut grouped: Vec<(Option<&str>, Vec<&Actions>)> = grouped
+        .into_iter()
+        .map(|(transaction, actions)| (Some(transaction), actions))
+        .chain(unknown)
+        .collect();
+    grouped.sort_by_key(|(_, actions)| std::cmp::Reverse(actions[0].ledger));
+    grouped
+}
+
+#[no_mangle]
+pub extern "C" fn transaction_activity() {
+    let env = EnvClient::empty();
+    let request: TransactionActivityRequest = params::read_request(&env);
+    let denominator = Denominator::new(&env, request.denomination);
+
+    let addresses = match watchlists::addresses(
+        &env,
+        request.address.into_iter().collect(),
+        request.watchlist,
+    ) {
+        Ok(addresses) => addresses,
+        Err(error) => {
+            env.conclude(&error);
+            return;
+        }
+    };
+    let actions: Vec<Actions> = if let Some(addresses) = addresses {
+        // Read the whole transactions the addresses took part in, not only
+        // their own actions.
+        let (known, mut actions): (Vec<Actions>, Vec<Actions>) =
+            positions::read_actions_for(&env, &addresses)
+                .into_iter()
+                .partition(|action| action.transaction.is_some());
+        let mut transactions: Vec<Option<String>> =
+            known.into_iter().map(|action| action.transaction).collect();
+        transactions.sort();
+        transactions.dedup();
+
+        for transaction in transactions {
+            let rows: Vec<Actions> = env
+                .read_filter()
+                .column_equal_to_bytes("transaction", &transaction.encode())
+                .read()
+                .unwrap();
+            actions.extend(rows);
+        }
+        actions
+    } else {
+        env.read()
+    };
+
+    let actions: Vec<Actions> = actions
+        .into_iter()
+        .filter(|action| request.from_ledger.is_none_or(|from| action.ledger >= from))
+        .filter(|action| request.to_ledger.is_none_or(|to| action.ledger <= to))
+        .collect();
+
+    let mut activity: Vec<TransactionActivity> = group(&actions)
+        .into_iter()
+        .map(|(transaction, actions)| {
+            let mut sources: Vec<String> =
+                actions.iter().map(|action| action.source.clone()).collect();
+            sources.sort();
+            sources.dedup();
+
+            TransactionActivity {
+                transaction: transaction.map(String::from),
+                operation: classify(&actions),
+                timestamp: actions[0].timestamp,
+                ledger: actions[0].ledger,
+                sources,
+                actions: actions
+                    .iter()
+                    .map(|action| ActionV2::new(action, &denominator))
+                    .collect(),
+            }
+        })
+        .collect();
+    if let Some(limit) = request.limit {
+        activity.truncate(limit);
+    }
+
+    env.conclude(&activity)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{classify, group, Operation};
+    use crate::{Action, Actions};
+
+    #[test]
+    fn operations() {
+        let supply = Actions::test(Action::Collateral, 100);
+        let withdraw = Actions::test(Action::Collateral, -100);
+        let borrow = Actions::test(Action::Borrow, 50);
+        let repay = Actions::test(Action::Borrow, -50);
+
+        assert_eq!(classify(&[&supply]), Operation::SimpleDeposit);
+        assert_eq!(
+            classify(&[&supply, &borrow, &supply]),
+            Operation::LeverageLoop
+        );
+        assert_eq!(classify(&[&repay, &withdraw]), Operation::Deleverage);
+        assert_eq!(classify(&[&repay]), Operation::Repayment);
+        assert_eq!(classify(&[&supply, &repay]), Operation::Rebalance);
+    }
+
+    #[test]
+    fn actions_without_transaction_are_not_grouped() {
+        let actions = vec![
+            Actions::test(Action::Collateral, 100).with_transaction("aa"),
+            Actions::test(Action::Borrow, 50).with_transaction("aa"),
+            Actions::test(Action::Collateral, 10),
+            Actions::test(Action::Borrow, 5),
+        ];
+
+        let grouped = group(&actions);
+        assert_eq!(grouped.len(), 3);
+        assert_eq!(grouped[0].0, Some("aa"));
+        assert_eq!(grouped[0].1.len(), 2);
+        assert_eq!(grouped[1].0, None);
+        assert_eq!(grouped[1].1.len(), 1);
+        assert_eq!(grouped[2].0, None);
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{prelude::*, AgnosticRequest, DatabaseDerive, EnvClient, Method};
+
+use crate::{db, describe, params, positions, snapshots::Snapshots, watchlists};
+
+/// Utilization levels that trigger an alert when crossed until others are
+/// configured, e.g. above 95% withdrawals may start failing.
+const DEFAULT_UTILIZATION_THRESHOLDS: &[f64] = &[0.9, 0.95];
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[repr(u32)]
+pub enum AlertKind {
+    Utilization,
+}
+
+describe::object! {
+    #[derive(DatabaseDerive, Serialize, Clone)]
+    #[with_name("alerts")]
+    pub struct Alerts {
+        pub kind: u32,
+        pub asset: String,
+        pub threshold: f64,
+        pub value: f64,
+        /// Whether the threshold was crossed upwards.
+        pub rising: bool,
+        pub timestamp: u64,
+        pub ledger: u32,
+    }
+}
+
+db::row!(
+    Alerts,
+    "alerts",
+    [kind, asset, threshold, value, rising, timestamp, ledger]
+);
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("alert_config")]
+/// Alerting configuration, a single row written by `configure_alerts`.
+pub struct AlertConfig {
+    pub id: u32,
+    /// Bincode-encoded `AlertSettings`.
+    pub settings: Vec<u8>,
+}
+
+const CONFIG_ID: u32 = 0;
+
+describe::object! {
+    /// Thresholds alerts are evaluated against and the channels they are
+    /// dispatched to. Every alert is stored in the `alerts` table, which the
+    /// `alerts` query reads, and posted as JSON to each webhook.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct AlertSettings {
+        pub utilization_thresholds: Vec<f64>,
+        pub webhooks: Vec<String>,
+    }
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            utilization_thresholds: DEFAULT_UTILIZATION_THRESHOLDS.to_vec(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl AlertSettings {
+    fn decode(config: Option<&AlertConfig>) -> Self {
+        config
+            .and_then(|config| bincode::deserialize(&config.settings).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn config(env: &EnvClient) -> Option<AlertConfig> {
+    let configs: Vec<AlertConfig> = env
+        .read_filter()
+        .column_equal_to("id", CONFIG_ID)
+        .read()
+        .unwrap();
+
+    configs.into_iter().next()
+}
+
+/// Returns whether `threshold` was crossed upwards (`Some(true)`) or
+/// downwards (`Some(false)`) when moving from `previous` to `current`.
+fn crossed(threshold: f64, previous: f64, current: f64) -> Option<bool> {
+    if previous < threshold && current >= threshold {
+        Some(true)
+    } else if previous >= threshold && current < threshold {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Stores the alert and posts it to the configured webhooks.
+fn dispatch(env: &EnvClient, settings: &AlertSettings, alert: &Alerts) {
+    db::put(env, alert);
+
+    for webhook in &settings.webhooks {
+        env.send_web_request(AgnosticRequest {
+            body: Some(serde_json::to_string(alert).unwrap()),
+            url: webhook.clone(),
+            method: Method::Post,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+        });
+    }
+}
+
+/// Dispatches an alert for every configured utilization threshold crossed
+/// between the previous and the new snapshot of an asset.
+pub fn utilization(env: &EnvClient, snapshots: &[(Option<Snapshots>, Snapshots)]) {
+    if snapshots.is_empty() {
+        return;
+    }
+    let settings = AlertSettings::decode(config(env).as_ref());
+
+    for (previous, current) in snapshots {
+        let previous = previous
+            .as_ref()
+            .map_or(0.0, |snapshot| snapshot.utilization());
+        let value = current.utilization();
+
+        for threshold in &settings.utilization_thresholds {
+            if let Some(rising) = crossed(*threshold, previous, value) {
+                dispatch(
+                    env,
+                    &settings,
+                    &Alerts {
+                        kind: AlertKind::Utilization as u32,
+                        asset: current.asset.clone(),
+                        threshold: *threshold,
+                        value,
+                        rising,
+                        timestamp: current.timestamp,
+                        ledger: current.ledger,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConfigureAlertsRequest {
+    /// Replaces the utilization thresholds, as fractions of the supply.
+    utilization_thresholds: Option<Vec<f64>>,
+    /// Replaces the webhook URLs alerts are posted to.
+    webhooks: Option<Vec<String>>,
+}
+
+#[no_mangle]
+pub extern "C" fn configure_alerts() {
+    let env = EnvClient::empty();
+    let request: ConfigureAlertsRequest = params::read_request(&env);
+
+    let existing = config(&env);
+    let mut settings = AlertSettings::decode(existing.as_ref());
+    if let Some(thresholds) = request.utilization_thresholds {
+        settings.utilization_thresholds = thresholds;
+    }
+    if let Some(webhooks) = request.webhooks {
+        settings.webhooks = webhooks;
+    }
+
+    let config = AlertConfig {
+        id: CONFIG_ID,
+        settings: bincode::serialize(&settings).unwrap(),
+    };
+    match existing {
+        Some(_) => env
+            .update()
+            .column_equal_to("id", CONFIG_ID)
+            .execute(&config)
+            .unwrap(),
+        None => env.put(&config),
+    }
+
+    env.conclude(&settings)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AlertsRequest {
+    kind: Option<AlertKind>,
+    asset: Option<String>,
+    /// Only return alerts on assets the watchlist's addresses have a
+    /// position in.
+    watchlist: Option<u32>,
+    /// Only return alerts dispatched at or after this ledger.
+    since: Option<u32>,
+}
+
+#[no_mangle]
+pub extern "C" fn alerts() {
+    let env = EnvClient::empty();
+    let request: AlertsRequest = params::read_request(&env);
+
+    let alerts: Vec<Alerts> = if let Some(asset) = request.asset {
+        env.read_filter()
+            .column_equal_to("asset", asset)
+            .read()
+            .unwrap()
+    } else {
+        env.read()
+    };
+
+    let watched = match watchlists::addresses(&env, Vec::new(), request.watchlist) {
+        Ok(watched) => watched,
+        Err(error) => {
+            env.conclude(&error);
+            return;
+        }
+    };
+    let watched_assets: Option<Vec<String>> = watched.map(|addresses| {
+        positions::read(&env, Some(&addresses))
+            .into_iter()
+            .map(|position| position.asset)
+            .collect()
+    });
+
+    let alerts: Vec<Alerts> = alerts
+        .into_iter()
+        .filter(|alert| {
+            watched_assets
+                .as_ref()
+                .is_none_or(|assets| assets.contains(&alert.asset))
+        })
+        .filter(|alert| request.kind.is_none_or(|kind| alert.kind == kind as u32))
+        .filter(|alert| request.since.is_none_or(|since| alert.ledger >= since))
+        .collect();
+
+    env.conclude(&alerts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crossed, AlertConfig, AlertSettings, CONFIG_ID};
+
+    #[test]
+    fn threshold_crossing() {
+        assert_eq!(crossed(0.95, 0.9, 0.96), Some(true));
+        assert_eq!(crossed(0.95, 0.9, 0.95), Some(true));
+        assert_eq!(crossed(0.95, 0.97, 0.8), Some(false));
+        assert_eq!(crossed(0.95, 0.96, 0.99), None);
+        assert_eq!(crossed(0.95, 0.5, 0.6), None);
+    }
+
+    #[test]
+    fn configured_settings() {
+        assert_eq!(AlertSettings::decode(None), AlertSettings::default());
+
+        let settings = AlertSettings {
+            utilization_thresholds: vec![0.8],
+            webhooks: vec!["https://alerts.example.com/blend".into()],
+        };
+        let config = AlertConfig {
+            id: CONFIG_ID,
+            settings: bincode::serialize(&settings).unwrap(),
+        };
+        assert_eq!(AlertSettings::decode(Some(&config)), settings);
+    }
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+
+use crate::{
+    denomination::{Amount, Denominator},
+    describe, Action, Actions,
+};
+
+/// Version assumed for requests that don't specify one.
+pub const DEFAULT_VERSION: u32 = 1;
+
+describe::object! {
+    /// Response wrapper used from v2 onwards.
+    #[derive(Serialize)]
+    pub struct Envelope<T> {
+        pub api_version: u32,
+        pub count: usize,
+        pub data: Vec<T>,
+    }
+}
+
+impl<T> Envelope<T> {
+    pub fn new(api_version: u32, data: Vec<T>) -> Self {
+        Self {
+            api_version,
+            count: data.len(),
+            data,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ApiError {
+    pub error: String,
+}
+
+impl ApiError {
+    pub fn unsupported_version(api_version: u32) -> Self {
+        Self {
+            error: format!("unsupported api_version {}", api_version),
+        }
+    }
+
+    pub fn unknown_watchlist(id: u32) -> Self {
+        Self {
+            error: format!("unknown watchlist {}", id),
+        }
+    }
+}
+
+describe::object! {
+    /// v1 representation of an indexed action, the flat `actions` row.
+    #[derive(Serialize)]
+    pub struct ActionV1 {
+        pub action: u32,
+        pub timestamp: u64,
+        pub ledger: u32,
+        pub asset: String,
+        pub source: String,
+        pub amount: Amount,
+    }
+}
+
+impl ActionV1 {
+    pub fn new(action: &Actions, denominator: &Denominator) -> Self {
+        Self {
+            action: action.action,
+            timestamp: action.timestamp,
+            ledger: action.ledger,
+            asset: action.asset.clone(),
+            source: action.source.clone(),
+            amount: denominator.amount(&action.asset, action.amount as i128),
+        }
+    }
+}
+
+describe::object! {
+    /// v2 representation of an indexed action.
+    #[derive(Serialize)]
+    pub struct ActionV2 {
+        pub kind: Option<Action>,
+        /// Whether the action increased the position, e.g. a borrow rather
+        /// than a repayment.
+        pub increase: bool,
+        pub timestamp: u64,
+        pub ledger: u32,
+        pub asset: String,
+        pub source: String,
+        pub amount: Amount,
+        /// USD value at the time of the action.
+        pub usd_value: Option<f64>,
+        pub transaction: Option<String>,
+    }
+}
+
+impl ActionV2 {
+    pub fn new(action: &Actions, denominator: &Denominator) -> Self {
+        Self {
+            kind: Action::try_from(action.action).ok(),
+            increase: action.amount >= 0,
+            timestamp: action.timestamp,
+            ledger: action.ledger,
+            asset: action.asset.clone(),
+            source: action.source.clone(),
+            amount: denominator.amount(&action.asset, action.amount as i128),
+            usd_value: action.usd_value,
+            transaction: action.transaction.clone(),
+        }
+    }
+}
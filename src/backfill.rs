@@ -0,0 +1,68 @@
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    db::{self, Encode},
+    prices,
+    reserves::RESERVES,
+    Actions,
+};
+
+/// Maximum number of rows backfilled by a single invocation.
+const BATCH_SIZE: usize = 100;
+
+/// Populates the USD value of previously indexed actions that lack one
+/// using the price closest in time to the action.
+///
+/// Only actions on configured reserves are read, those on other assets can
+/// never be priced. Actions without a price within `MAX_PRICE_DISTANCE`
+/// are left untouched.
+pub fn usd_values(env: &EnvClient) {
+    let mut backfilled = 0;
+    for reserve in RESERVES {
+        if backfilled == BATCH_SIZE {
+            break;
+        }
+
+        let missing: Vec<Actions> = env
+            .read_filter()
+            .column_equal_to("asset", reserve.asset.to_string())
+            .column_equal_to_bytes("usd_value", &None::<f64>.encode())
+            .read()
+            .unwrap();
+        if missing.is_empty() {
+            continue;
+        }
+
+        let history = prices::history(env, reserve.asset);
+        for mut action in missing {
+            if backfilled == BATCH_SIZE {
+                break;
+            }
+
+            action.usd_value = prices::historical_usd_value(
+                &history,
+                &action.asset,
+                action.amount as i128,
+                action.timestamp,
+            );
+            if action.usd_value.is_none() {
+                continue;
+            }
+
+            db::update(
+                env,
+                &action,
+                &[
+                    "action",
+                    "timestamp",
+                    "ledger",
+                    "asset",
+                    "source",
+                    "amount",
+                    "transaction",
+                ],
+            );
+            backfilled += 1;
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::metrics;
+
+/// Column value encoded the same way `DatabaseDerive` encodes it: numbers,
+/// strings and bytes are wrapped in a `ZephyrVal`, other types are plain
+/// bincode.
+pub trait Encode {
+    fn encode(&self) -> Vec<u8>;
+}
+
+macro_rules! zephyr_val {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self) -> Vec<u8> {
+                    to_zephyr_val(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+zephyr_val!(i64, i128, u64, f64, u32, i32, f32, String, Vec<u8>);
+
+impl<T: Serialize> Encode for Option<T> {
+    fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
+
+fn to_zephyr_val(value: impl Into<ZephyrVal>) -> Vec<u8> {
+    bincode::serialize(&value.into()).unwrap()
+}
+
+/// Table row that can be written through the failure-capturing helpers.
+pub trait Row {
+    const TABLE: &'static str;
+    const COLUMNS: &'static [&'static str];
+
+    fn segments(&self) -> Vec<Vec<u8>>;
+}
+
+macro_rules! row {
+    ($row:ty, $table:literal, [$($column:ident),*]) => {
+        impl $crate::db::Row for $row {
+            const TABLE: &'static str = $table;
+            const COLUMNS: &'static [&'static str] = &[$(stringify!($column)),*];
+
+            fn segments(&self) -> Vec<Vec<u8>> {
+                vec![$($crate::db::Encode::encode(&self.$column)),*]
+            }
+        }
+    };
+}
+
+pub(crate) use row;
+
+/// Number of attempts after which a failed write is abandoned.
+const MAX_ATTEMPTS: u32 = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(u32)]
+pub enum FailureState {
+    Pending,
+    Resolved,
+    /// Failed `MAX_ATTEMPTS` times and is no longer retried.
+    Abandoned,
+}
+
+/// Columns and encoded values of a failed insert.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Payload {
+    columns: Vec<String>,
+    segments: Vec<Vec<u8>>,
+}
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("write_failures")]
+/// Insert that couldn't be performed, kept to be retried on the next
+/// invocations.
+pub struct WriteFailures {
+    pub id: u64,
+    pub target_table: String,
+    /// Bincode-encoded `Payload`.
+    pub payload: Vec<u8>,
+    pub error: String,
+    pub attempts: u32,
+    pub state: u32,
+}
+
+impl WriteFailures {
+    fn new(table: &str, columns: &[&str], segments: Vec<Vec<u8>>, error: String) -> Self {
+        let payload = bincode::serialize(&Payload {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            segments,
+        })
+        .unwrap();
+
+        // FNV-1a over the payload, good enough to tell failures apart.
+        let mut id: u64 = 0xcbf29ce484222325;
+        for byte in table.bytes().chain(payload.iter().copied()) {
+            id ^= byte as u64;
+            id = id.wrapping_mul(0x100000001b3);
+        }
+
+        Self {
+            id,
+            target_table: table.into(),
+            payload,
+            error,
+            attempts: 1,
+            state: FailureState::Pending as u32,
+        }
+    }
+
+    fn write(&self, env: &EnvClient) -> Result<(), String> {
+        let payload: Payload =
+            bincode::deserialize(&self.payload).map_err(|error| error.to_string())?;
+        let columns: Vec<&str> = payload.columns.iter().map(String::as_str).collect();
+        write(env, &self.target_table, &columns, &payload.segments)
+    }
+}
+
+fn write(
+    env: &EnvClient,
+    table: &str,
+    columns: &[&str],
+    segments: &[Vec<u8>],
+) -> Result<(), String> {
+    let segments: Vec<&[u8]> = segments.iter().map(Vec::as_slice).collect();
+    let result = env.db_write(table, columns, &segments);
+
+    metrics::count_write(result.is_ok());
+    result.map_err(|error| format!("{:?}", error))
+}
+
+/// Inserts a row, returning the error instead of capturing it when the
+/// write fails.
+pub fn try_put<T: Row>(env: &EnvClient, row: &T) -> Result<(), String> {
+    write(env, T::TABLE, T::COLUMNS, &row.segments())
+}
+
+/// Updates the row whose `keys` columns match the given row, returning the
+/// error like [`try_put`].
+pub fn try_update<T: Row>(env: &EnvClient, row: &T, keys: &[&str]) -> Result<(), String> {
+    let segments = row.segments();
+    let conditions: Vec<Condition> = T::COLUMNS
+        .iter()
+        .zip(&segments)
+        .filter(|(column, _)| keys.contains(column))
+        .map(|(column, segment)| Condition::ColumnEqualTo(column.to_string(), segment.clone()))
+        .collect();
+    let segments: Vec<&[u8]> = segments.iter().map(Vec::as_slice).collect();
+    let result = env.db_update(T::TABLE, T::COLUMNS, &segments, &conditions);
+
+    metrics::count_write(result.is_ok());
+    result.map_err(|error| format!("{:?}", error))
+}
+
+/// Inserts a row. A failed write doesn't abort the invocation, the row is
+/// stored in `write_failures` and retried on the next invocation.
+pub fn put<T: Row>(env: &EnvClient, row: &T) {
+    let segments = row.segments();
+    if let Err(error) = write(env, T::TABLE, T::COLUMNS, &segments) {
+        env.log()
+            .error(format!("write to {} failed: {}", T::TABLE, error), None);
+        env.put(&WriteFailures::new(T::TABLE, T::COLUMNS, segments, error));
+    }
+}
+
+/// Updates the row whose `keys` columns match the given row.
+///
+/// Unlike [`put`], a failed update is only logged: keyed rows hold state
+/// that later invocations rewrite, and replaying a stale update could
+/// overwrite a newer value.
+pub fn update<T: Row>(env: &EnvClient, row: &T, keys: &[&str]) {
+    if let Err(error) = try_update(env, row, keys) {
+        env.log()
+            .error(format!("update of {} failed: {}", T::TABLE, error), None);
+    }
+}
+
+/// Retries the inserts that failed during previous invocations, giving up
+/// on a write after `MAX_ATTEMPTS` attempts.
+pub fn retry_failures(env: &EnvClient) {
+    let failures: Vec<WriteFailures> = env
+        .read_filter()
+        .column_equal_to("state", FailureState::Pending as u32)
+        .read()
+        .unwrap();
+
+    for mut failure in failures {
+        match failure.write(env) {
+            Ok(()) => failure.state = FailureState::Resolved as u32,
+            Err(error) => {
+                failure.attempts += 1;
+                failure.error = error;
+                if failure.attempts >= MAX_ATTEMPTS {
+                    failure.state = FailureState::Abandoned as u32;
+                    env.log().error(
+                        format!(
+                            "giving up on write to {} after {} attempts: {}",
+                            failure.target_table, failure.attempts, failure.error
+                        ),
+                        None,
+                    );
+                }
+            }
+        }
+
+        if env
+            .update()
+            .column_equal_to("id", failure.id)
+            .execute(&failure)
+            .is_err()
+        {
+            env.log().error(
+                format!("couldn't update write failure {}", failure.id),
+                None,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Payload, WriteFailures};
+
+    #[test]
+    fn failure_payload_round_trip() {
+        let failure = WriteFailures::new(
+            "actions",
+            &["action", "amount"],
+            vec![vec![1], vec![2, 3]],
+            "unavailable".into(),
+        );
+
+        let payload: Payload = bincode::deserialize(&failure.payload).unwrap();
+        assert_eq!(payload.columns, vec!["action", "amount"]);
+        assert_eq!(payload.segments, vec![vec![1], vec![2, 3]]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{prices, reserves};
+
+/// Decimals assumed for assets that aren't configured as pool reserves.
+const DEFAULT_DECIMALS: u32 = 7;
+
+/// How amounts are expressed in responses.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Denomination {
+    /// Raw integer amounts in the token's smallest unit.
+    #[default]
+    Raw,
+    /// Decimal strings scaled by the token's decimals.
+    Decimal,
+    /// USD values at the latest indexed price.
+    Usd,
+    /// XLM values at the latest indexed prices.
+    Xlm,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Amount {
+    Raw(i128),
+    Decimal(String),
+    /// `None` when the asset has no indexed price.
+    Usd(Option<f64>),
+    /// `None` when the asset or XLM have no indexed price.
+    Xlm(Option<f64>),
+}
+
+/// Converts raw amounts to the requested denomination.
+pub struct Denominator {
+    denomination: Denomination,
+    prices: BTreeMap<String, i128>,
+}
+
+impl Denominator {
+    pub fn new(env: &EnvClient, denomination: Option<Denomination>) -> Self {
+        let denomination = denomination.unwrap_or_default();
+        let prices = match denomination {
+            Denomination::Usd | Denomination::Xlm => prices::latest(env),
+            Denomination::Raw | Denomination::Decimal => BTreeMap::new(),
+        };
+
+        Self {
+            denomination,
+            prices,
+        }
+    }
+
+    pub fn amount(&self, asset: &str, amount: i128) -> Amount {
+        match self.denomination {
+            Denomination::Raw => Amount::Raw(amount),
+            Denomination::Decimal => {
+                let decimals =
+                    reserves::get(asset).map_or(DEFAULT_DECIMALS, |reserve| reserve.decimals);
+                Amount::Decimal(to_decimal(amount, decimals))
+            }
+            Denomination::Usd => Amount::Usd(self.usd_value(asset, amount)),
+            Denomination::Xlm => Amount::Xlm(self.usd_value(asset, amount).and_then(|usd_value| {
+                let xlm_price = self.prices.get(reserves::XLM)?;
+                prices::xlm_value(usd_value, *xlm_price)
+            })),
+        }
+    }
+
+    fn usd_value(&self, asset: &str, amount: i128) -> Option<f64> {
+        prices::latest_usd_value(&self.prices, asset, amount)
+    }
+}
+
+fn to_decimal(amount: i128, decimals: u32) -> String {
+    let scalar = 10u128.pow(decimals);
+    let sign = if amount < 0 { "-" } else { "" };
+    let amount = amount.unsigned_abs();
+
+    if decimals == 0 {
+        format!("{}{}", sign, amount)
+    } else {
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            amount / scalar,
+            amount % scalar,
+            width = decimals as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_decimal;
+
+    #[test]
+    fn decimal_amounts() {
+        assert_eq!(to_decimal(1000000000, 7), "100.0000000");
+        assert_eq!(to_decimal(-5, 7), "-0.0000005");
+        assert_eq!(to_decimal(123, 0), "123");
+    }
+}
@@ -0,0 +1,815 @@
+use serde::{
+    de::{
+        self, value::Error, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer,
+        MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    ser::{self, Impossible},
+    Serialize, Serializer,
+};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    activity::{self, TransactionActivity},
+    alerts::{self, AlertSettings, Alerts},
+    api::{ActionV1, ActionV2, Envelope},
+    denomination::Amount,
+    events::{self, Events},
+    flows::{self, DailyFlowsView},
+    health::{self, HealthDistribution},
+    planner::{self, RepaymentPlan},
+    portfolio::{self, Portfolio},
+    rank::{self, RankResponse},
+    status::{Status, StatusResponse},
+    tvl::{self, Tvl},
+    watchlists::{self, Watchlist},
+    Request,
+};
+
+/// Shape of a request or response.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Schema {
+    Any,
+    Bool,
+    Integer,
+    Number,
+    String,
+    Optional { of: Box<Schema> },
+    Array { items: Box<Schema> },
+    Object { fields: Vec<Field> },
+    Enum { variants: Vec<&'static str> },
+    OneOf { of: Vec<Schema> },
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Field {
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+/// Schema of a request, traced from the `Deserialize` implementation of
+/// the type it is read into.
+pub fn request_schema<T: DeserializeOwned>() -> Schema {
+    let mut tracer = Tracer(Schema::Any);
+    // Tracing feeds placeholder values, types rejecting them are described
+    // up to the point they failed.
+    let _ = T::deserialize(&mut tracer);
+    tracer.0
+}
+
+/// Deserializer recording the schema requested by the type being
+/// deserialized, handing out placeholder values.
+struct Tracer(Schema);
+
+impl Tracer {
+    fn trace<'de, T: DeserializeSeed<'de>>(seed: T) -> Result<(T::Value, Schema), Error> {
+        let mut tracer = Tracer(Schema::Any);
+        let value = seed.deserialize(&mut tracer)?;
+        Ok((value, tracer.0))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Tracer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Any;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Bool;
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Integer;
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Integer;
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Number;
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::String;
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::String;
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut inner = Tracer(Schema::Any);
+        let value = visitor.visit_some(&mut inner);
+        self.0 = Schema::Optional {
+            of: Box::new(inner.0),
+        };
+        value
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut items = Single {
+            items: None,
+            done: false,
+        };
+        let value = visitor.visit_seq(&mut items);
+        self.0 = Schema::Array {
+            items: Box::new(items.items.unwrap_or(Schema::Any)),
+        };
+        value
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0 = Schema::Object { fields: Vec::new() };
+        visitor.visit_map(&mut Fields {
+            names: &[],
+            fields: Vec::new(),
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let mut access = Fields {
+            names: fields,
+            fields: Vec::new(),
+        };
+        let value = visitor.visit_map(&mut access);
+        self.0 = Schema::Object {
+            fields: access.fields,
+        };
+        value
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0 = Schema::Enum {
+            variants: variants.to_vec(),
+        };
+        visitor.visit_enum(UnitVariant(variants.first().copied().unwrap_or_default()))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// Sequence yielding a single traced element.
+struct Single {
+    items: Option<Schema>,
+    done: bool,
+}
+
+impl<'de> SeqAccess<'de> for &mut Single {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+
+        let (value, schema) = Tracer::trace(seed)?;
+        self.items = Some(schema);
+        Ok(Some(value))
+    }
+}
+
+/// Map yielding every field of a struct with a traced value.
+struct Fields {
+    names: &'static [&'static str],
+    fields: Vec<Field>,
+}
+
+impl<'de> MapAccess<'de> for &mut Fields {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.names.get(self.fields.len()) {
+            Some(name) => seed
+                .deserialize(IntoDeserializer::<Error>::into_deserializer(*name))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let name = self.names[self.fields.len()];
+        let (value, schema) = Tracer::trace(seed)?;
+        self.fields.push(Field { name, schema });
+        Ok(value)
+    }
+}
+
+/// Picks the given unit variant of an enum.
+struct UnitVariant(&'static str);
+
+impl<'de> EnumAccess<'de> for UnitVariant {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.0))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        Tracer::trace(seed).map(|(value, _)| value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom("tuple variants can't be traced"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::custom("struct variants can't be traced"))
+    }
+}
+
+/// Schema of a response type, following its `Serialize` implementation.
+///
+/// Responses are only ever serialized, so unlike requests their schema
+/// can't be traced from `Deserialize`. Structs and enums implement it
+/// through `object!` and `unit_enum!`.
+pub trait Describe {
+    fn schema() -> Schema;
+}
+
+macro_rules! describe_as {
+    ($schema:ident: $($ty:ty),*) => {
+        $(
+            impl Describe for $ty {
+                fn schema() -> Schema {
+                    Schema::$schema
+                }
+            }
+        )*
+    };
+}
+
+describe_as!(Bool: bool);
+describe_as!(Integer: u32, u64, usize, i128);
+describe_as!(Number: f64);
+describe_as!(String: String);
+
+impl<T: Describe> Describe for Option<T> {
+    fn schema() -> Schema {
+        Schema::Optional {
+            of: Box::new(T::schema()),
+        }
+    }
+}
+
+impl<T: Describe> Describe for Vec<T> {
+    fn schema() -> Schema {
+        Schema::Array {
+            items: Box::new(T::schema()),
+        }
+    }
+}
+
+impl Describe for Amount {
+    fn schema() -> Schema {
+        // Untagged, the amount is serialized as the variant's value.
+        let _variants = |amount: &Amount| match amount {
+            Amount::Raw(_) | Amount::Decimal(_) | Amount::Usd(_) | Amount::Xlm(_) => (),
+        };
+
+        Schema::OneOf {
+            of: vec![i128::schema(), String::schema(), Option::<f64>::schema()],
+        }
+    }
+}
+
+/// Defines a struct and describes it as an object with its fields, so the
+/// schema is generated from the definition itself. Fields are expected to
+/// be serialized under their own name.
+///
+/// Field types are matched as an identifier rather than a `ty` fragment
+/// so derives on the struct, such as `DatabaseDerive`, can still inspect
+/// them.
+macro_rules! object {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $ty:ident $(<$param:ident>)? {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident: $field_ty:ident $(<$field_param:ty>)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $ty $(<$param>)? {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $field_ty $(<$field_param>)?,
+            )*
+        }
+
+        impl $(<$param: $crate::describe::Describe>)? $crate::describe::Describe
+            for $ty $(<$param>)?
+        {
+            fn schema() -> $crate::describe::Schema {
+                $crate::describe::Schema::Object {
+                    fields: vec![$($crate::describe::Field {
+                        name: stringify!($field),
+                        schema: <$field_ty $(<$field_param>)? as $crate::describe::Describe>::schema(),
+                    }),*],
+                }
+            }
+        }
+    };
+}
+
+/// Defines a fieldless enum and describes it by the names its variants
+/// serialize to.
+macro_rules! unit_enum {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $ty:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis enum $ty {
+            $(
+                $(#[$variant_attr])*
+                $variant,
+            )*
+        }
+
+        impl $crate::describe::Describe for $ty {
+            fn schema() -> $crate::describe::Schema {
+                $crate::describe::Schema::Enum {
+                    variants: vec![$($crate::describe::variant_name(&$ty::$variant)),*],
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use object;
+pub(crate) use unit_enum;
+
+/// Name a unit variant is serialized as.
+pub fn variant_name<T: Serialize>(value: &T) -> &'static str {
+    value.serialize(VariantName).unwrap()
+}
+
+/// Serializer accepting only unit variants, returning their name.
+struct VariantName;
+
+macro_rules! reject {
+    ($($method:ident($($ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<&'static str, Error> {
+                Err(ser::Error::custom("not a unit variant"))
+            }
+        )*
+    };
+}
+
+impl Serializer for VariantName {
+    type Ok = &'static str;
+    type Error = Error;
+    type SerializeSeq = Impossible<&'static str, Error>;
+    type SerializeTuple = Impossible<&'static str, Error>;
+    type SerializeTupleStruct = Impossible<&'static str, Error>;
+    type SerializeTupleVariant = Impossible<&'static str, Error>;
+    type SerializeMap = Impossible<&'static str, Error>;
+    type SerializeStruct = Impossible<&'static str, Error>;
+    type SerializeStructVariant = Impossible<&'static str, Error>;
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<&'static str, Error> {
+        Ok(variant)
+    }
+
+    reject!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<&'static str, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<&'static str, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<&'static str, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(ser::Error::custom("not a unit variant"))
+    }
+}
+
+#[derive(Serialize)]
+pub struct Response {
+    /// `api_version` the response shape applies to, if versioned.
+    pub api_version: Option<u32>,
+    pub schema: Schema,
+}
+
+#[derive(Serialize)]
+pub struct Endpoint {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// `None` for endpoints that don't read a request body. Requests can
+    /// also be sent as key/value parameters with the same names.
+    pub request: Option<Schema>,
+    pub responses: Vec<Response>,
+}
+
+impl Endpoint {
+    fn new<Req: DeserializeOwned, Res: Describe>(
+        name: &'static str,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            request: Some(request_schema::<Req>()),
+            ..Self::without_request::<Res>(name, description)
+        }
+    }
+
+    fn without_request<Res: Describe>(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            request: None,
+            responses: vec![Response {
+                api_version: None,
+                schema: Res::schema(),
+            }],
+        }
+    }
+}
+
+pub fn catalog() -> Vec<Endpoint> {
+    vec![
+        Endpoint {
+            name: "retrieve",
+            description: "Indexed actions of a kind.",
+            request: Some(request_schema::<Request>()),
+            responses: vec![
+                Response {
+                    api_version: Some(1),
+                    schema: Vec::<ActionV1>::schema(),
+                },
+                Response {
+                    api_version: Some(2),
+                    schema: Envelope::<ActionV2>::schema(),
+                },
+            ],
+        },
+        Endpoint::new::<portfolio::PortfolioRequest, Portfolio>(
+            "portfolio",
+            "Combined positions, exposure and activity of a set of addresses.",
+        ),
+        Endpoint::new::<health::HealthDistributionRequest, HealthDistribution>(
+            "health_distribution",
+            "Number of users per health factor bucket.",
+        ),
+        Endpoint::new::<alerts::ConfigureAlertsRequest, AlertSettings>(
+            "configure_alerts",
+            "Replaces the alert thresholds and webhooks.",
+        ),
+        Endpoint::new::<alerts::AlertsRequest, Vec<Alerts>>("alerts", "Dispatched alerts."),
+        Endpoint::new::<events::RawEventsRequest, Vec<Events>>(
+            "raw_events",
+            "Raw pool events by ledger range or transaction.",
+        ),
+        Endpoint::without_request::<Vec<Status>>(
+            "validate_config",
+            "Validates the configured contracts.",
+        ),
+        Endpoint::without_request::<StatusResponse>(
+            "status",
+            "Configuration checks and processing statistics.",
+        ),
+        Endpoint::new::<rank::RankRequest, RankResponse>(
+            "percentile_rank",
+            "Rank of a user's debt and collateral among all users.",
+        ),
+        Endpoint::new::<flows::DailyFlowsRequest, Vec<DailyFlowsView>>(
+            "daily_flows",
+            "Per-asset daily flows.",
+        ),
+        Endpoint::new::<activity::TransactionActivityRequest, Vec<TransactionActivity>>(
+            "transaction_activity",
+            "Activity grouped by transaction with the inferred operation.",
+        ),
+        Endpoint::new::<tvl::TvlRequest, Tvl>("tvl", "Value locked per asset in USD and XLM."),
+        Endpoint::new::<watchlists::CreateWatchlistRequest, Watchlist>(
+            "create_watchlist",
+            "Creates a named watchlist of addresses.",
+        ),
+        Endpoint::new::<watchlists::UpdateWatchlistRequest, Watchlist>(
+            "update_watchlist",
+            "Renames, edits or deletes a watchlist.",
+        ),
+        Endpoint::new::<watchlists::WatchlistsRequest, Vec<Watchlist>>(
+            "watchlists",
+            "Existing watchlists.",
+        ),
+        Endpoint::new::<planner::RepaymentPlanRequest, RepaymentPlan>(
+            "repayment_plan",
+            "Amounts to repay or supply to reach a target health factor.",
+        ),
+    ]
+}
+
+#[no_mangle]
+pub extern "C" fn describe() {
+    let env = EnvClient::empty();
+
+    env.conclude(catalog())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{catalog, request_schema, Describe, Field, Schema};
+    use crate::{activity::Operation, api::ActionV1, denomination::Amount, Request};
+
+    #[test]
+    fn traces_request() {
+        let Schema::Object { fields } = request_schema::<Request>() else {
+            panic!("expected an object");
+        };
+
+        assert_eq!(
+            fields[0],
+            Field {
+                name: "kind",
+                schema: Schema::Enum {
+                    variants: vec!["Borrow", "Collateral"]
+                }
+            }
+        );
+        assert_eq!(
+            fields[1],
+            Field {
+                name: "address",
+                schema: Schema::Optional {
+                    of: Box::new(Schema::String)
+                }
+            }
+        );
+        assert!(fields.iter().any(|field| field.name == "denomination"));
+    }
+
+    #[test]
+    fn describes_serialized_responses() {
+        let Schema::Object { fields } = ActionV1::schema() else {
+            panic!("expected an object");
+        };
+        let amount = fields.iter().find(|field| field.name == "amount").unwrap();
+        assert_eq!(amount.schema, Amount::schema());
+        assert_ne!(amount.schema, Schema::Any);
+
+        let Schema::Enum { variants } = Operation::schema() else {
+            panic!("expected an enum");
+        };
+        assert_eq!(variants[0], "simple_deposit");
+        assert_eq!(variants.len(), 7);
+    }
+
+    /// Every exported endpoint is in the catalog and vice versa.
+    #[test]
+    fn catalog_matches_exports() {
+        let mut exported = Vec::new();
+        let src = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+        for file in fs::read_dir(src).unwrap() {
+            let source = fs::read_to_string(file.unwrap().path()).unwrap();
+            let mut lines = source.lines();
+            while let Some(line) = lines.next() {
+                if line.trim() != "#[no_mangle]" {
+                    continue;
+                }
+                let function = lines.next().unwrap();
+                let name = function
+                    .trim()
+                    .strip_prefix("pub extern \"C\" fn ")
+                    .and_then(|rest| rest.split('(').next())
+                    .unwrap();
+                if name != "on_close" {
+                    exported.push(name.to_string());
+                }
+            }
+        }
+        exported.sort();
+
+        let mut catalogued: Vec<String> = catalog()
+            .iter()
+            .map(|endpoint| endpoint.name.to_string())
+            .collect();
+        catalogued.push("describe".into());
+        catalogued.sort();
+
+        assert_eq!(catalogued, exported);
+    }
+}
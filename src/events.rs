@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{
+    prelude::*,
+    soroban_sdk::xdr::{ContractEventBody, Hash, ScVal, ScVec, TransactionMeta},
+    DatabaseDerive, EnvClient,
+};
+
+use crate::{db, describe, params};
+
+describe::object! {
+    #[derive(DatabaseDerive, Serialize, Clone)]
+    #[with_name("events")]
+    /// Raw contract event as emitted by the pool, kept around to debug
+    /// decoding discrepancies. Topics and data are base64 XDR.
+    pub struct Events {
+        pub transaction: String,
+        /// The topics as a single `ScVal::Vec`.
+        pub topics: String,
+        pub data: String,
+        pub timestamp: u64,
+        pub ledger: u32,
+    }
+}
+
+db::row!(
+    Events,
+    "events",
+    [transaction, topics, data, timestamp, ledger]
+);
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Contract event along with the hash of the transaction that emitted it.
+#[derive(Clone)]
+pub struct TransactionEvent {
+    pub transaction: String,
+    pub topics: Vec<ScVal>,
+    pub data: ScVal,
+}
+
+/// Encodes the topics as a single base64 XDR `ScVal::Vec`.
+fn encode_topics(topics: &[ScVal]) -> String {
+    ScVal::Vec(Some(ScVec(topics.to_vec().try_into().unwrap())))
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+}
+
+/// Events emitted by `contract` in the current ledger.
+pub fn contract_events(env: &EnvClient, contract: [u8; 32]) -> Vec<TransactionEvent> {
+    let mut events = Vec::new();
+    let contract = Hash(contract);
+
+    for processing in env.reader().tx_processing() {
+        let TransactionMeta::V3(meta) = &processing.tx_apply_processing else {
+            continue;
+        };
+        let Some(soroban_meta) = &meta.soroban_meta else {
+            continue;
+        };
+
+        let transaction = to_hex(&processing.result.transaction_hash.0);
+        for event in soroban_meta.events.iter() {
+            if event.contract_id.as_ref() != Some(&contract) {
+                continue;
+            }
+            let ContractEventBody::V0(body) = &event.body;
+
+            events.push(TransactionEvent {
+                transaction: transaction.clone(),
+                topics: body.topics.to_vec(),
+                data: body.data.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+/// Stores the raw events.
+pub fn index(env: &EnvClient, events: &[TransactionEvent]) {
+    for event in events {
+        db::put(
+            env,
+            &Events {
+                transaction: event.transaction.clone(),
+                topics: encode_topics(&event.topics),
+                data: event.data.to_xdr_base64(Limits::none()).unwrap(),
+                timestamp: env.reader().ledger_timestamp(),
+                ledger: env.reader().ledger_sequence(),
+            },
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawEventsRequest {
+    /// Hex encoded transaction hash.
+    transaction: Option<String>,
+    from_ledger: Option<u32>,
+    to_ledger: Option<u32>,
+}
+
+#[no_mangle]
+pub extern "C" fn raw_events() {
+    let env = EnvClient::empty();
+    let request: RawEventsRequest = params::read_request(&env);
+
+    let events: Vec<Events> = if let Some(transaction) = request.transaction {
+        env.read_filter()
+            .column_equal_to("transaction", transaction.to_lowercase())
+            .read()
+            .unwrap()
+    } else {
+        env.read()
+    };
+
+    let events: Vec<Events> = events
+        .into_iter()
+        .filter(|event| request.from_ledger.is_none_or(|from| event.ledger >= from))
+        .filter(|event| request.to_ledger.is_none_or(|to| event.ledger <= to))
+        .collect();
+
+    env.conclude(&events)
+}
+
+#[cfg(test)]
+mod test {
+    use zephyr_sdk::soroban_sdk::xdr::{Limits, ReadXdr, ScSymbol, ScVal, ScVec};
+
+    use super::encode_topics;
+
+    #[test]
+    fn topics_round_trip() {
+        let topics = vec![
+            ScVal::Symbol(ScSymbol("borrow".try_into().unwrap())),
+            ScVal::U32(7),
+        ];
+
+        let decoded = ScVal::from_xdr_base64(encode_topics(&topics), Limits::none()).unwrap();
+        assert_eq!(decoded, ScVal::Vec(Some(ScVec(topics.try_into().unwrap()))));
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{
+    db,
+    denomination::{Amount, Denomination, Denominator},
+    describe, params, views, Action, Actions,
+};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(DatabaseDerive, Serialize, Clone, Debug, PartialEq)]
+#[with_name("daily_flows")]
+/// Per-asset flows over a UTC day, kept as a materialized view in the
+/// `daily_flows` table.
+pub struct DailyFlows {
+    pub asset: String,
+    /// Days since the unix epoch.
+    pub day: u64,
+    pub supplied: i128,
+    pub withdrawn: i128,
+    pub borrowed: i128,
+    pub repaid: i128,
+}
+
+db::row!(
+    DailyFlows,
+    "daily_flows",
+    [asset, day, supplied, withdrawn, borrowed, repaid]
+);
+
+impl DailyFlows {
+    fn new(asset: String, day: u64) -> Self {
+        Self {
+            asset,
+            day,
+            supplied: 0,
+            withdrawn: 0,
+            borrowed: 0,
+            repaid: 0,
+        }
+    }
+
+    fn apply(&mut self, action: &Actions) {
+        let amount = action.amount as i128;
+        if action.action == Action::Collateral as u32 {
+            if amount >= 0 {
+                self.supplied += amount;
+            } else {
+                self.withdrawn -= amount;
+            }
+        } else if action.action == Action::Borrow as u32 {
+            if amount >= 0 {
+                self.borrowed += amount;
+            } else {
+                self.repaid -= amount;
+            }
+        }
+    }
+}
+
+impl views::ViewRow for DailyFlows {
+    const KEYS: &'static [&'static str] = &["asset", "day"];
+
+    fn merge(&mut self, other: &Self) {
+        self.supplied += other.supplied;
+        self.withdrawn += other.withdrawn;
+        self.borrowed += other.borrowed;
+        self.repaid += other.repaid;
+    }
+}
+
+/// Aggregates actions into per-asset daily flows.
+pub fn flows(actions: &[Actions]) -> Vec<DailyFlows> {
+    let mut flows: BTreeMap<(String, u64), DailyFlows> = BTreeMap::new();
+    for action in actions {
+        let day = action.timestamp / SECONDS_PER_DAY;
+        flows
+            .entry((action.asset.clone(), day))
+            .or_insert_with(|| DailyFlows::new(action.asset.clone(), day))
+            .apply(action);
+    }
+
+    flows.into_values().collect()
+}
+
+/// Writes the flows resulting from `actions` to the `daily_flows` view,
+/// on top of the existing flows when `incremental`.
+pub fn refresh(env: &EnvClient, actions: &[Actions], incremental: bool) -> Result<(), String> {
+    views::write(env, flows(actions), incremental, |flows| {
+        let existing: Vec<DailyFlows> = env
+            .read_filter()
+            .column_equal_to("asset", flows.asset.clone())
+            .column_equal_to("day", flows.day)
+            .read()
+            .unwrap();
+        existing.into_iter().next()
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyFlowsRequest {
+    asset: Option<String>,
+    /// First day to include, in days since the unix epoch.
+    from_day: Option<u64>,
+    to_day: Option<u64>,
+    denomination: Option<Denomination>,
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct DailyFlowsView {
+        pub asset: String,
+        pub day: u64,
+        pub supplied: Amount,
+        pub withdrawn: Amount,
+        pub borrowed: Amount,
+        pub repaid: Amount,
+    }
+}
+
+impl DailyFlowsView {
+    fn new(flows: &DailyFlows, denominator: &Denominator) -> Self {
+        Self {
+            asset: flows.asset.clone(),
+            day: flows.day,
+            supplied: denominator.amount(&flows.asset, flows.supplied),
+            withdrawn: denominator.amount(&flows.asset, flows.withdrawn),
+            borrowed: denominator.amount(&flows.asset, flows.borrowed),
+            repaid: denominator.amount(&flows.asset, flows.repaid),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daily_flows() {
+    let env = EnvClient::empty();
+    let request: DailyFlowsRequest = params::read_request(&env);
+    let denominator = Denominator::new(&env, request.denomination);
+
+    let flows: Vec<DailyFlows> = match (views::is_fresh(&env, views::DAILY_FLOWS), request.asset) {
+        (true, Some(asset)) => env
+            .read_filter()
+            .column_equal_to("asset", asset)
+            .read()
+            .unwrap(),
+        (true, None) => env.read(),
+        (false, Some(asset)) => {
+            let actions: Vec<Actions> = env
+                .read_filter()
+                .column_equal_to("asset", asset)
+                .read()
+                .unwrap();
+            flows(&actions)
+        }
+        (false, None) => flows(&env.read::<Actions>()),
+    };
+
+    let flows: Vec<DailyFlowsView> = flows
+        .iter()
+        .filter(|flows| request.from_day.is_none_or(|from| flows.day >= from))
+        .filter(|flows| request.to_day.is_none_or(|to| flows.day <= to))
+        .map(|flows| DailyFlowsView::new(flows, &denominator))
+        .collect();
+
+    env.conclude(&flows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::flows;
+    use crate::{Action, Actions};
+
+    #[test]
+    fn daily_aggregation() {
+        let flows = flows(&[
+            Actions::test(Action::Collateral, 1000).with_timestamp(10),
+            Actions::test(Action::Collateral, -300).with_timestamp(20),
+            Actions::test(Action::Borrow, 200).with_timestamp(30),
+            Actions::test(Action::Borrow, -50).with_timestamp(86_400),
+        ]);
+
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].day, 0);
+        assert_eq!(flows[0].supplied, 1000);
+        assert_eq!(flows[0].withdrawn, 300);
+        assert_eq!(flows[0].borrowed, 200);
+        assert_eq!(flows[1].day, 1);
+        assert_eq!(flows[1].repaid, 50);
+    }
+}
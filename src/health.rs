@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    describe, params,
+    pool::{self, PoolReserve, SCALAR_7},
+    positions::{self, Position},
+    prices,
+    reserves::{self, RESERVES},
+    Actions,
+};
+
+/// Default health factor bucket bounds.
+const DEFAULT_BOUNDS: &[f64] = &[1.05, 1.2, 1.5, 2.0];
+
+/// USD value of an account's collateral weighted by the collateral factors
+/// and of its liabilities weighted by the liability factors, as configured
+/// on the pool's reserves.
+///
+/// Positions on assets that aren't configured, held by the pool or priced
+/// are ignored.
+pub fn effective_values(
+    positions: &[Position],
+    prices: &BTreeMap<String, i128>,
+    pool_reserves: &[PoolReserve],
+) -> (f64, f64) {
+    let mut collateral = 0.0;
+    let mut liabilities = 0.0;
+
+    for position in positions {
+        let (Some(reserve), Some(pool_reserve), Some(price)) = (
+            reserves::get(&position.asset),
+            pool_reserves
+                .iter()
+                .find(|pool_reserve| pool_reserve.asset == position.asset),
+            prices.get(&position.asset),
+        ) else {
+            continue;
+        };
+
+        collateral += prices::usd_value(reserve, position.collateral, *price)
+            * pool_reserve.config.c_factor as f64
+            / SCALAR_7 as f64;
+        liabilities += prices::usd_value(reserve, position.liabilities, *price) * SCALAR_7 as f64
+            / pool_reserve.config.l_factor as f64;
+    }
+
+    (collateral, liabilities)
+}
+
+/// Computes the health factor of a single account given its positions,
+/// i.e. the ratio between its effective collateral and liabilities.
+///
+/// Returns `None` for accounts without liabilities.
+pub fn health_factor(
+    positions: &[Position],
+    prices: &BTreeMap<String, i128>,
+    pool_reserves: &[PoolReserve],
+) -> Option<f64> {
+    let (collateral, liabilities) = effective_values(positions, prices, pool_reserves);
+    if liabilities > 0.0 {
+        Some(collateral / liabilities)
+    } else {
+        None
+    }
+}
+
+/// Positions of every account that interacted with the pool, as stored by
+/// the pool at the current rates, which include the interest accrued since
+/// the indexed actions. Accounts without open positions are left out.
+pub fn by_account(
+    env: &EnvClient,
+    pool_reserves: &[PoolReserve],
+) -> BTreeMap<String, Vec<Position>> {
+    positions::accounts(&env.read::<Actions>())
+        .into_iter()
+        .map(|account| {
+            let positions = pool::user_positions(env, &account).underlying(&account, pool_reserves);
+            (account, positions)
+        })
+        .filter(|(_, positions)| !positions.is_empty())
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthDistributionRequest {
+    /// Ascending bucket bounds, defaults to `DEFAULT_BOUNDS`.
+    bounds: Option<Vec<f64>>,
+}
+
+describe::object! {
+    #[derive(Serialize, Debug, PartialEq)]
+    pub struct Bucket {
+        /// Inclusive lower bound, `None` for the first bucket.
+        pub lower: Option<f64>,
+        /// Exclusive upper bound, `None` for the last bucket.
+        pub upper: Option<f64>,
+        pub users: u32,
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct HealthDistribution {
+        pub buckets: Vec<Bucket>,
+        /// Users that currently have collateral but no outstanding
+        /// liabilities.
+        pub no_liabilities: u32,
+    }
+}
+
+fn buckets(bounds: &[f64], health_factors: &[f64]) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = (0..=bounds.len())
+        .map(|idx| Bucket {
+            lower: idx.checked_sub(1).map(|lower| bounds[lower]),
+            upper: bounds.get(idx).copied(),
+            users: 0,
+        })
+        .collect();
+
+    for health_factor in health_factors {
+        let idx = bounds
+            .iter()
+            .position(|bound| health_factor < bound)
+            .unwrap_or(bounds.len());
+        buckets[idx].users += 1;
+    }
+
+    buckets
+}
+
+#[no_mangle]
+pub extern "C" fn health_distribution() {
+    let env = EnvClient::empty();
+    let request: HealthDistributionRequest = params::read_request(&env);
+    let bounds = request.bounds.unwrap_or_else(|| DEFAULT_BOUNDS.to_vec());
+
+    let prices = prices::latest(&env);
+    let pool_reserves = pool::reserves(&env, RESERVES.iter().map(|reserve| reserve.asset));
+
+    let mut health_factors = Vec::new();
+    let mut no_liabilities = 0;
+    for positions in by_account(&env, &pool_reserves).values() {
+        match health_factor(positions, &prices, &pool_reserves) {
+            Some(health_factor) => health_factors.push(health_factor),
+            None => no_liabilities += 1,
+        }
+    }
+
+    env.conclude(&HealthDistribution {
+        buckets: buckets(&bounds, &health_factors),
+        no_liabilities,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{buckets, Bucket};
+
+    #[test]
+    fn bucketing() {
+        let buckets = buckets(&[1.05, 1.2], &[0.9, 1.05, 1.1, 1.3, 5.0]);
+        assert_eq!(
+            buckets,
+            vec![
+                Bucket {
+                    lower: None,
+                    upper: Some(1.05),
+                    users: 1
+                },
+                Bucket {
+                    lower: Some(1.05),
+                    upper: Some(1.2),
+                    users: 2
+                },
+                Bucket {
+                    lower: Some(1.2),
+                    upper: None,
+                    users: 2
+                },
+            ]
+        );
+    }
+}
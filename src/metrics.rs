@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::Serialize;
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{db, describe};
+
+/// Number of most recent invocations kept, and which the statistics are
+/// computed over.
+const RECENT_INVOCATIONS: u32 = 1000;
+
+static ROWS_WRITTEN: AtomicU32 = AtomicU32::new(0);
+static FAILED_WRITES: AtomicU32 = AtomicU32::new(0);
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("invocations")]
+/// Rows written by a single `on_close` invocation.
+///
+/// The table is a ring of `RECENT_INVOCATIONS` rows, each invocation
+/// overwrites the row of its `slot`.
+pub struct Invocations {
+    pub slot: u32,
+    pub rows: u32,
+    pub failures: u32,
+    pub timestamp: u64,
+    pub ledger: u32,
+}
+
+db::row!(
+    Invocations,
+    "invocations",
+    [slot, rows, failures, timestamp, ledger]
+);
+
+pub fn count_write(ok: bool) {
+    if ok {
+        ROWS_WRITTEN.fetch_add(1, Ordering::Relaxed);
+    } else {
+        FAILED_WRITES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records the writes of the current invocation.
+pub fn record(env: &EnvClient) {
+    let ledger = env.reader().ledger_sequence();
+    let invocation = Invocations {
+        slot: ledger % RECENT_INVOCATIONS,
+        rows: ROWS_WRITTEN.load(Ordering::Relaxed),
+        failures: FAILED_WRITES.load(Ordering::Relaxed),
+        timestamp: env.reader().ledger_timestamp(),
+        ledger,
+    };
+
+    let existing: Vec<Invocations> = env
+        .read_filter()
+        .column_equal_to("slot", invocation.slot)
+        .read()
+        .unwrap();
+    if existing.is_empty() {
+        db::put(env, &invocation);
+    } else {
+        db::update(env, &invocation, &["slot"]);
+    }
+}
+
+describe::object! {
+    #[derive(Serialize, Debug, PartialEq, Default)]
+    pub struct Percentiles {
+        pub p50: u32,
+        pub p90: u32,
+        pub p99: u32,
+        pub max: u32,
+    }
+}
+
+fn percentiles(mut values: Vec<u32>) -> Percentiles {
+    if values.is_empty() {
+        return Percentiles::default();
+    }
+    values.sort_unstable();
+
+    let at = |percentile: usize| values[(values.len() - 1) * percentile / 100];
+    Percentiles {
+        p50: at(50),
+        p90: at(90),
+        p99: at(99),
+        max: values[values.len() - 1],
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct WriteStats {
+        pub invocations: usize,
+        pub last_ledger: Option<u32>,
+        pub rows: Percentiles,
+        pub failures: Percentiles,
+    }
+}
+
+/// Statistics over the most recent invocations.
+pub fn recent(env: &EnvClient) -> WriteStats {
+    let mut invocations = env.read::<Invocations>();
+    invocations.sort_by_key(|invocation| std::cmp::Reverse(invocation.ledger));
+
+    WriteStats {
+        invocations: invocations.len(),
+        last_ledger: invocations.first().map(|invocation| invocation.ledger),
+        rows: percentiles(invocations.iter().map(|i| i.rows).collect()),
+        failures: percentiles(invocations.iter().map(|i| i.failures).collect()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{percentiles, Percentiles};
+
+    #[test]
+    fn percentiles_of_values() {
+        assert_eq!(percentiles(vec![]), Percentiles::default());
+        assert_eq!(
+            percentiles((1..=100).rev().collect()),
+            Percentiles {
+                p50: 50,
+                p90: 90,
+                p99: 99,
+                max: 100
+            }
+        );
+    }
+}
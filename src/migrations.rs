@@ -0,0 +1,164 @@
+use serde::Serialize;
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::db::{self, Encode};
+
+/// Columns added to a table that already held rows.
+///
+/// The host can't read a row that has no value for one of the requested
+/// columns, so the rows written before the columns existed are given a
+/// default before anything reads the table.
+struct Migration {
+    /// Identifies the migration in the `migrations` table.
+    name: &'static str,
+    table: &'static str,
+    /// Column the existing rows are addressed by. One update is issued per
+    /// distinct value, so it should have few of them.
+    key: &'static str,
+    columns: &'static [&'static str],
+    defaults: fn() -> Vec<Vec<u8>>,
+}
+
+fn no_usd_value() -> Vec<Vec<u8>> {
+    vec![None::<f64>.encode()]
+}
+
+fn no_transaction() -> Vec<Vec<u8>> {
+    vec![None::<String>.encode()]
+}
+
+fn no_tvl() -> Vec<Vec<u8>> {
+    vec![None::<f64>.encode(), None::<f64>.encode()]
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "actions_usd_value",
+        table: "actions",
+        key: "action",
+        columns: &["usd_value"],
+        defaults: no_usd_value,
+    },
+    Migration {
+        name: "actions_transaction",
+        table: "actions",
+        key: "action",
+        columns: &["transaction"],
+        defaults: no_transaction,
+    },
+    Migration {
+        name: "snapshots_tvl",
+        table: "snapshots",
+        key: "asset",
+        columns: &["tvl_usd", "tvl_xlm"],
+        defaults: no_tvl,
+    },
+];
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("migrations")]
+/// Migration applied to the tables, with the ledger it was applied at.
+pub struct Migrations {
+    pub name: String,
+    pub ledger: u32,
+}
+
+db::row!(Migrations, "migrations", [name, ledger]);
+
+impl Migration {
+    fn apply(&self, env: &EnvClient) -> Result<(), String> {
+        let rows = env
+            .db_read(self.table, &[self.key], None, None)
+            .map_err(|error| format!("{:?}", error))?;
+        let defaults = (self.defaults)();
+        let segments: Vec<&[u8]> = defaults.iter().map(Vec::as_slice).collect();
+
+        for key in distinct(
+            rows.rows
+                .iter()
+                .filter_map(|row| row.row.first())
+                .map(|value| value.0.as_slice()),
+        ) {
+            env.db_update(
+                self.table,
+                self.columns,
+                &segments,
+                &[Condition::ColumnEqualTo(self.key.to_string(), key)],
+            )
+            .map_err(|error| format!("{:?}", error))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn distinct<'a>(values: impl Iterator<Item = &'a [u8]>) -> Vec<Vec<u8>> {
+    let mut values: Vec<Vec<u8>> = values.map(<[u8]>::to_vec).collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// Applies the migrations that weren't applied yet. Runs before anything
+/// else reads or writes the tables in the invocation.
+pub fn run(env: &EnvClient) {
+    let applied: Vec<String> = env
+        .read::<Migrations>()
+        .into_iter()
+        .map(|migration| migration.name)
+        .collect();
+
+    for migration in MIGRATIONS {
+        if applied.iter().any(|name| name == migration.name) {
+            continue;
+        }
+
+        match migration.apply(env) {
+            Ok(()) => db::put(
+                env,
+                &Migrations {
+                    name: migration.name.into(),
+                    ledger: env.reader().ledger_sequence(),
+                },
+            ),
+            Err(error) => env.log().error(
+                format!("migration {} failed: {}", migration.name, error),
+                None,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{db::Encode, Action};
+
+    use super::{distinct, MIGRATIONS};
+
+    #[test]
+    fn existing_rows_are_updated_once_per_key() {
+        // `action` column of rows written before `usd_value` existed.
+        let stored = [
+            (Action::Collateral as u32).encode(),
+            (Action::Borrow as u32).encode(),
+            (Action::Collateral as u32).encode(),
+        ];
+
+        let keys = distinct(stored.iter().map(Vec::as_slice));
+        assert_eq!(
+            keys,
+            vec![
+                (Action::Borrow as u32).encode(),
+                (Action::Collateral as u32).encode()
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_match_columns() {
+        for migration in MIGRATIONS {
+            assert_eq!(migration.columns.len(), (migration.defaults)().len());
+        }
+        assert_eq!((MIGRATIONS[0].defaults)(), vec![None::<f64>.encode()]);
+    }
+}
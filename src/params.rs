@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    de::{
+        self,
+        value::{Error, MapDeserializer, SeqDeserializer},
+        DeserializeOwned, IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize,
+};
+use zephyr_sdk::EnvClient;
+
+/// Request payload, either a JSON body or key/value parameters, the latter
+/// as a query string (`kind=Borrow&address=G...`) or as an object of
+/// string values. Lists are comma separated or given as repeated keys.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Payload<T> {
+    Query(String),
+    Json(T),
+    Params(BTreeMap<String, String>),
+}
+
+/// Reads the request, accepting both JSON bodies and key/value parameters.
+pub fn read_request<T: DeserializeOwned>(env: &EnvClient) -> T {
+    match env.read_request_body::<Payload<T>>() {
+        Payload::Json(request) => request,
+        Payload::Query(query) => from_query(&query).unwrap(),
+        Payload::Params(params) => from_params(params.into_iter()).unwrap(),
+    }
+}
+
+pub fn from_query<T: DeserializeOwned>(query: &str) -> Result<T, Error> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (decode(key), decode(value)),
+            None => (decode(pair), String::new()),
+        });
+
+    from_params(params)
+}
+
+fn from_params<T: DeserializeOwned>(
+    params: impl Iterator<Item = (String, String)>,
+) -> Result<T, Error> {
+    let mut merged: BTreeMap<String, String> = BTreeMap::new();
+    for (key, value) in params {
+        let key = key.strip_suffix("[]").map(str::to_string).unwrap_or(key);
+        merged
+            .entry(key)
+            .and_modify(|existing| {
+                existing.push(',');
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+
+    T::deserialize(MapDeserializer::new(
+        merged.into_iter().map(|(key, value)| (key, Param(value))),
+    ))
+}
+
+/// Decodes `+` and percent-encoded bytes.
+fn decode(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'+' => decoded.push(b' '),
+            b'%' if idx + 2 < bytes.len() => {
+                let byte = std::str::from_utf8(&bytes[idx + 1..idx + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        idx += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Single parameter value, parsed according to the type it's deserialized
+/// into.
+struct Param(String);
+
+impl<'de> IntoDeserializer<'de, Error> for Param {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::custom(format!("invalid value {}", self.0))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Param {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items: Vec<Param> = self
+            .0
+            .split(',')
+            .filter(|item| !item.is_empty())
+            .map(|item| Param(item.to_string()))
+            .collect();
+
+        visitor.visit_seq(SeqDeserializer::new(items.into_iter()))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::from_query;
+    use crate::Action;
+
+    #[derive(Deserialize)]
+    struct Request {
+        kind: Action,
+        address: Option<String>,
+        addresses: Vec<String>,
+        limit: Option<usize>,
+        bounds: Option<Vec<f64>>,
+    }
+
+    #[test]
+    fn query_string() {
+        let request: Request =
+            from_query("?kind=Borrow&address=G%2BA&addresses[]=A&addresses[]=B&bounds=1.05,1.2")
+                .unwrap();
+
+        assert!(matches!(request.kind, Action::Borrow));
+        assert_eq!(request.address.as_deref(), Some("G+A"));
+        assert_eq!(request.addresses, vec!["A", "B"]);
+        assert_eq!(request.limit, None);
+        assert_eq!(request.bounds, Some(vec![1.05, 1.2]));
+
+        assert!(from_query::<Request>("kind=Unknown&addresses=A").is_err());
+    }
+}
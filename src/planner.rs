@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    denomination::{Amount, Denomination, Denominator},
+    describe, health, params,
+    pool::{self, PoolReserve, SCALAR_7},
+    positions::Position,
+    prices,
+    reserves::{self, RESERVES},
+};
+
+describe::unit_enum! {
+    #[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Step {
+        Repay,
+        Supply,
+    }
+}
+
+/// Single-asset action that brings the account to the target health
+/// factor on its own.
+#[derive(Debug, PartialEq)]
+struct Suggestion {
+    asset: String,
+    step: Step,
+    amount: i128,
+    usd_value: f64,
+}
+
+/// Computes, for every reserve of the pool, how much the account would
+/// need to repay or supply to reach `target`.
+///
+/// Repayments are only suggested for assets the account owes enough of.
+/// Positions are the underlying amounts of the account's b/dTokens at the
+/// reserves' current rates.
+fn plan(
+    positions: &[Position],
+    prices: &BTreeMap<String, i128>,
+    pool_reserves: &[PoolReserve],
+    target: f64,
+) -> Vec<Suggestion> {
+    let (collateral, liabilities) = health::effective_values(positions, prices, pool_reserves);
+    if liabilities <= 0.0 || collateral / liabilities >= target {
+        return Vec::new();
+    }
+
+    let to_amount = |usd_value: f64, decimals: u32, price: i128| -> i128 {
+        (usd_value * 10f64.powi((decimals + prices::ORACLE_DECIMALS) as i32) / price as f64).ceil()
+            as i128
+    };
+
+    let mut options = Vec::new();
+    for pool_reserve in pool_reserves {
+        let config = &pool_reserve.config;
+        let (Some(reserve), Some(price)) = (
+            reserves::get(&pool_reserve.asset),
+            prices
+                .get(&pool_reserve.asset)
+                .copied()
+                .filter(|price| *price > 0),
+        ) else {
+            continue;
+        };
+
+        // collateral / (liabilities - repaid / l_factor) = target
+        let repaid = (liabilities - collateral / target) * config.l_factor as f64 / SCALAR_7 as f64;
+        let owed = positions
+            .iter()
+            .filter(|position| position.asset == reserve.asset)
+            .map(|position| prices::usd_value(reserve, position.liabilities, price))
+            .sum::<f64>();
+        if owed >= repaid {
+            options.push(Suggestion {
+                asset: reserve.asset.into(),
+                step: Step::Repay,
+                amount: to_amount(repaid, reserve.decimals, price),
+                usd_value: repaid,
+            });
+        }
+
+        // (collateral + supplied * c_factor) / liabilities = target
+        if config.c_factor > 0 {
+            let supplied =
+                (target * liabilities - collateral) * SCALAR_7 as f64 / config.c_factor as f64;
+            options.push(Suggestion {
+                asset: reserve.asset.into(),
+                step: Step::Supply,
+                amount: to_amount(supplied, reserve.decimals, price),
+                usd_value: supplied,
+            });
+        }
+    }
+
+    options
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RepaymentPlanRequest {
+    address: String,
+    target_health_factor: f64,
+    /// Denomination of the suggested amounts.
+    denomination: Option<Denomination>,
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct PlanOption {
+        pub asset: String,
+        pub step: Step,
+        pub amount: Amount,
+        pub usd_value: f64,
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct RepaymentPlan {
+        pub address: String,
+        pub health_factor: Option<f64>,
+        pub target_health_factor: f64,
+        /// Alternative single-asset actions, each reaching the target on its
+        /// own. Empty when the account is already at or above the target.
+        pub options: Vec<PlanOption>,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn repayment_plan() {
+    let env = EnvClient::empty();
+    let request: RepaymentPlanRequest = params::read_request(&env);
+    let denominator = Denominator::new(&env, request.denomination);
+
+    let prices = prices::latest(&env);
+    let pool_reserves = pool::reserves(&env, RESERVES.iter().map(|reserve| reserve.asset));
+    let positions =
+        pool::user_positions(&env, &request.address).underlying(&request.address, &pool_reserves);
+
+    let options = plan(
+        &positions,
+        &prices,
+        &pool_reserves,
+        request.target_health_factor,
+    )
+    .into_iter()
+    .map(|option| PlanOption {
+        amount: denominator.amount(&option.asset, option.amount),
+        asset: option.asset,
+        step: option.step,
+        usd_value: option.usd_value,
+    })
+    .collect();
+
+    env.conclude(&RepaymentPlan {
+        address: request.address,
+        health_factor: health::health_factor(&positions, &prices, &pool_reserves),
+        target_health_factor: request.target_health_factor,
+        options,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{plan, Step};
+    use crate::{
+        health,
+        pool::{PoolReserve, ReserveConfig, ReserveData, SCALAR_9},
+        positions::Position,
+        prices::ORACLE_DECIMALS,
+        reserves::RESERVES,
+    };
+
+    fn pool_reserve(asset: &str, index: u32, c_factor: u32, l_factor: u32) -> PoolReserve {
+        PoolReserve {
+            asset: asset.into(),
+            config: ReserveConfig {
+                index,
+                c_factor,
+                l_factor,
+            },
+            data: ReserveData {
+                b_rate: SCALAR_9,
+                d_rate: SCALAR_9,
+                b_supply: 0,
+                d_supply: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn reaches_target() {
+        let xlm = RESERVES[0].asset;
+        let usdc = RESERVES[1].asset;
+        let pool_reserves = vec![
+            pool_reserve(xlm, 0, 7_500_000, 7_500_000),
+            pool_reserve(usdc, 1, 9_500_000, 9_500_000),
+        ];
+        let prices: BTreeMap<String, i128> = [
+            (xlm.to_string(), 10i128.pow(ORACLE_DECIMALS) / 10),
+            (usdc.to_string(), 10i128.pow(ORACLE_DECIMALS)),
+        ]
+        .into_iter()
+        .collect();
+        let positions = vec![
+            Position {
+                source: "A".into(),
+                asset: xlm.into(),
+                collateral: 10_000 * 10_000_000,
+                liabilities: 0,
+            },
+            Position {
+                source: "A".into(),
+                asset: usdc.into(),
+                collateral: 0,
+                liabilities: 700 * 10_000_000,
+            },
+        ];
+
+        let options = plan(&positions, &prices, &pool_reserves, 1.2);
+        assert!(!options.is_empty());
+        for option in options {
+            let mut positions = positions.clone();
+            let position = positions
+                .iter_mut()
+                .find(|position| position.asset == option.asset)
+                .unwrap();
+            match option.step {
+                Step::Repay => position.liabilities -= option.amount,
+                Step::Supply => position.collateral += option.amount,
+            }
+
+            let health_factor = health::health_factor(&positions, &prices, &pool_reserves).unwrap();
+            assert!((1.2..1.2001).contains(&health_factor));
+        }
+
+        assert!(plan(&positions, &prices, &pool_reserves, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use zephyr_sdk::{
+    soroban_sdk::xdr::{
+        AccountId, Hash, LedgerEntryData, PublicKey, ScAddress, ScSymbol, ScVal, ScVec, Uint256,
+    },
+    EnvClient,
+};
+
+use crate::{positions::Position, CONTRACT};
+
+/// Fixed point scalar of the pool's reserve factors.
+pub const SCALAR_7: i128 = 10_000_000;
+
+/// Fixed point scalar of the pool's b/dToken rates.
+pub const SCALAR_9: i128 = 1_000_000_000;
+
+/// Configuration of a reserve as stored by the pool under
+/// `ResConfig(asset)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReserveConfig {
+    /// Position of the reserve in the pool's reserve list, which keys the
+    /// users' positions.
+    pub index: u32,
+    /// Collateral factor, scaled by `SCALAR_7`.
+    pub c_factor: u32,
+    /// Liability factor, scaled by `SCALAR_7`.
+    pub l_factor: u32,
+}
+
+impl ReserveConfig {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        Some(Self {
+            index: u32_field(val, "index")?,
+            c_factor: u32_field(val, "c_factor")?,
+            l_factor: u32_field(val, "l_factor")?,
+        })
+    }
+}
+
+/// State of a reserve as stored by the pool under `ResData(asset)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReserveData {
+    /// Underlying per bToken.
+    pub b_rate: i128,
+    /// Underlying per dToken.
+    pub d_rate: i128,
+    pub b_supply: i128,
+    pub d_supply: i128,
+}
+
+impl ReserveData {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        Some(Self {
+            b_rate: i128_field(val, "b_rate")?,
+            d_rate: i128_field(val, "d_rate")?,
+            b_supply: i128_field(val, "b_supply")?,
+            d_supply: i128_field(val, "d_supply")?,
+        })
+    }
+
+    /// Underlying supplied to the reserve, used as collateral or not.
+    pub fn total_supply(&self) -> i128 {
+        b_to_underlying(self.b_supply, self.b_rate)
+    }
+
+    /// Underlying owed to the reserve, including accrued interest.
+    pub fn total_liabilities(&self) -> i128 {
+        d_to_underlying(self.d_supply, self.d_rate)
+    }
+}
+
+/// Converts bTokens to underlying, rounding down like the pool does.
+pub fn b_to_underlying(b_tokens: i128, b_rate: i128) -> i128 {
+    b_tokens * b_rate / SCALAR_9
+}
+
+/// Converts dTokens to underlying, rounding up like the pool does.
+pub fn d_to_underlying(d_tokens: i128, d_rate: i128) -> i128 {
+    let product = d_tokens * d_rate;
+    product / SCALAR_9 + (product % SCALAR_9 > 0) as i128
+}
+
+fn field<'a>(val: &'a ScVal, name: &str) -> Option<&'a ScVal> {
+    let ScVal::Map(Some(map)) = val else {
+        return None;
+    };
+
+    map.iter()
+        .find(
+            |entry| matches!(&entry.key, ScVal::Symbol(key) if key.0.as_slice() == name.as_bytes()),
+        )
+        .map(|entry| &entry.val)
+}
+
+fn i128_field(val: &ScVal, name: &str) -> Option<i128> {
+    as_i128(field(val, name)?)
+}
+
+fn u32_field(val: &ScVal, name: &str) -> Option<u32> {
+    match field(val, name)? {
+        ScVal::U32(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn as_i128(val: &ScVal) -> Option<i128> {
+    match val {
+        ScVal::I128(parts) => Some(((parts.hi as i128) << 64) | parts.lo as i128),
+        _ => None,
+    }
+}
+
+/// Reads a `Map<u32, i128>` field, such as the balances of a position
+/// keyed by reserve index.
+fn balances_field(val: &ScVal, name: &str) -> Option<BTreeMap<u32, i128>> {
+    let ScVal::Map(Some(map)) = field(val, name)? else {
+        return None;
+    };
+
+    map.iter()
+        .map(|entry| match &entry.key {
+            ScVal::U32(index) => Some((*index, as_i128(&entry.val)?)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Splits a `DataKey(address)` storage key into its name and address.
+fn keyed_address(key: &ScVal) -> Option<(&[u8], &ScAddress)> {
+    let ScVal::Vec(Some(parts)) = key else {
+        return None;
+    };
+
+    match parts.as_slice() {
+        [ScVal::Symbol(name), ScVal::Address(address)] => Some((name.0.as_slice(), address)),
+        _ => None,
+    }
+}
+
+fn pool_id() -> [u8; 32] {
+    stellar_strkey::Contract::from_string(CONTRACT).unwrap().0
+}
+
+fn pool() -> ScAddress {
+    ScAddress::Contract(Hash(pool_id()))
+}
+
+fn address(strkey: &str) -> Option<ScAddress> {
+    match stellar_strkey::Strkey::from_string(strkey).ok()? {
+        stellar_strkey::Strkey::PublicKeyEd25519(key) => Some(ScAddress::Account(AccountId(
+            PublicKey::PublicKeyTypeEd25519(Uint256(key.0)),
+        ))),
+        stellar_strkey::Strkey::Contract(contract) => Some(ScAddress::Contract(Hash(contract.0))),
+        _ => None,
+    }
+}
+
+fn keyed(name: &str, address: ScAddress) -> ScVal {
+    ScVal::Vec(Some(ScVec(
+        vec![
+            ScVal::Symbol(ScSymbol(name.try_into().unwrap())),
+            ScVal::Address(address),
+        ]
+        .try_into()
+        .unwrap(),
+    )))
+}
+
+/// Reads the pool's `DataKey(address)` entry.
+fn read_keyed(env: &EnvClient, name: &str, address: ScAddress) -> Option<ScVal> {
+    let entry = env
+        .read_contract_entry_by_scvalkey(pool_id(), keyed(name, address))
+        .ok()??;
+    match entry.entry.data {
+        LedgerEntryData::ContractData(data) => Some(data.val),
+        _ => None,
+    }
+}
+
+/// Reserve of the pool with its current on-chain configuration and state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolReserve {
+    pub asset: String,
+    pub config: ReserveConfig,
+    pub data: ReserveData,
+}
+
+/// Reads the configuration and state of the given reserves from the pool's
+/// ledger entries, skipping those the pool doesn't hold.
+pub fn reserves<'a>(
+    env: &EnvClient,
+    assets: impl IntoIterator<Item = &'a str>,
+) -> Vec<PoolReserve> {
+    assets
+        .into_iter()
+        .filter_map(|asset| {
+            let address = address(asset)?;
+            let config =
+                ReserveConfig::from_scval(&read_keyed(env, "ResConfig", address.clone())?)?;
+            let data = ReserveData::from_scval(&read_keyed(env, "ResData", address)?)?;
+            Some(PoolReserve {
+                asset: asset.into(),
+                config,
+                data,
+            })
+        })
+        .collect()
+}
+
+/// bToken collateral and dToken liabilities of a user, keyed by reserve
+/// index, as stored by the pool under `Positions(user)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UserPositions {
+    pub liabilities: BTreeMap<u32, i128>,
+    pub collateral: BTreeMap<u32, i128>,
+}
+
+impl UserPositions {
+    fn from_scval(val: &ScVal) -> Option<Self> {
+        Some(Self {
+            liabilities: balances_field(val, "liabilities")?,
+            collateral: balances_field(val, "collateral")?,
+        })
+    }
+
+    /// Underlying amounts of the positions at the reserves' current rates.
+    pub fn underlying(&self, user: &str, reserves: &[PoolReserve]) -> Vec<Position> {
+        reserves
+            .iter()
+            .map(|reserve| {
+                let index = reserve.config.index;
+                Position {
+                    source: user.into(),
+                    asset: reserve.asset.clone(),
+                    collateral: b_to_underlying(
+                        self.collateral.get(&index).copied().unwrap_or(0),
+                        reserve.data.b_rate,
+                    ),
+                    liabilities: d_to_underlying(
+                        self.liabilities.get(&index).copied().unwrap_or(0),
+                        reserve.data.d_rate,
+                    ),
+                }
+            })
+            .filter(|position| position.collateral != 0 || position.liabilities != 0)
+            .collect()
+    }
+}
+
+/// Current positions of a user as stored by the pool, empty if the user
+/// has none.
+pub fn user_positions(env: &EnvClient, user: &str) -> UserPositions {
+    address(user)
+        .and_then(|address| read_keyed(env, "Positions", address))
+        .and_then(|val| UserPositions::from_scval(&val))
+        .unwrap_or_default()
+}
+
+/// Reserves whose state was written by the pool in the current ledger.
+pub fn reserve_changes(env: &EnvClient) -> Vec<(String, ReserveData)> {
+    let pool = pool();
+    let changes = env.reader().v1_success_ledger_entries();
+
+    let mut reserves = Vec::new();
+    for entry in changes.created.iter().chain(changes.updated.iter()) {
+        let LedgerEntryData::ContractData(data) = &entry.data else {
+            continue;
+        };
+        if data.contract != pool {
+            continue;
+        }
+        let Some((b"ResData", ScAddress::Contract(asset))) = keyed_address(&data.key) else {
+            continue;
+        };
+
+        if let Some(reserve) = ReserveData::from_scval(&data.val) {
+            reserves.push((stellar_strkey::Contract(asset.0).to_string(), reserve));
+        }
+    }
+
+    reserves
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{
+        d_to_underlying, PoolReserve, ReserveConfig, ReserveData, UserPositions, SCALAR_9,
+    };
+
+    #[test]
+    fn reserve_totals() {
+        let reserve = ReserveData {
+            b_rate: SCALAR_9 + SCALAR_9 / 10,
+            d_rate: SCALAR_9 + SCALAR_9 / 4,
+            b_supply: 1_000,
+            d_supply: 600,
+        };
+
+        assert_eq!(reserve.total_supply(), 1_100);
+        assert_eq!(reserve.total_liabilities(), 750);
+        assert_eq!(d_to_underlying(3, SCALAR_9 / 2), 2);
+    }
+
+    #[test]
+    fn positions_at_current_rates() {
+        let reserve = |asset: &str, index: u32, b_rate: i128, d_rate: i128| PoolReserve {
+            asset: asset.into(),
+            config: ReserveConfig {
+                index,
+                c_factor: 0,
+                l_factor: 0,
+            },
+            data: ReserveData {
+                b_rate,
+                d_rate,
+                b_supply: 0,
+                d_supply: 0,
+            },
+        };
+        let reserves = vec![
+            reserve("XLM", 0, SCALAR_9 * 2, SCALAR_9),
+            reserve("USDC", 1, SCALAR_9, SCALAR_9 + SCALAR_9 / 2),
+            reserve("EURC", 2, SCALAR_9, SCALAR_9),
+        ];
+        let user = UserPositions {
+            liabilities: BTreeMap::from([(1, 100)]),
+            collateral: BTreeMap::from([(0, 1_000)]),
+        };
+
+        let positions = user.underlying("A", &reserves);
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].asset, "XLM");
+        assert_eq!(positions[0].collateral, 2_000);
+        assert_eq!(positions[1].asset, "USDC");
+        assert_eq!(positions[1].liabilities, 150);
+    }
+}
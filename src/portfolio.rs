@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    api::ActionV1,
+    denomination::{Amount, Denomination, Denominator},
+    describe, params,
+    positions::{self, Exposure, Position},
+    views, watchlists,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct PortfolioRequest {
+    #[serde(default)]
+    addresses: Vec<String>,
+    /// Include the addresses of a watchlist.
+    watchlist: Option<u32>,
+    /// Maximum number of activity entries to return, most recent first.
+    limit: Option<usize>,
+    denomination: Option<Denomination>,
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct PositionView {
+        pub source: String,
+        pub asset: String,
+        pub collateral: Amount,
+        pub liabilities: Amount,
+    }
+}
+
+impl PositionView {
+    fn new(position: &Position, denominator: &Denominator) -> Self {
+        Self {
+            source: position.source.clone(),
+            asset: position.asset.clone(),
+            collateral: denominator.amount(&position.asset, position.collateral),
+            liabilities: denominator.amount(&position.asset, position.liabilities),
+        }
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct ExposureView {
+        pub asset: String,
+        pub collateral: Amount,
+        pub liabilities: Amount,
+        pub net: Amount,
+    }
+}
+
+impl ExposureView {
+    fn new(exposure: &Exposure, denominator: &Denominator) -> Self {
+        Self {
+            asset: exposure.asset.clone(),
+            collateral: denominator.amount(&exposure.asset, exposure.collateral),
+            liabilities: denominator.amount(&exposure.asset, exposure.liabilities),
+            net: denominator.amount(&exposure.asset, exposure.net),
+        }
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct Portfolio {
+        pub positions: Vec<PositionView>,
+        pub exposure: Vec<ExposureView>,
+        pub activity: Vec<ActionV1>,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn portfolio() {
+    let env = EnvClient::empty();
+    let request: PortfolioRequest = params::read_request(&env);
+    let denominator = Denominator::new(&env, request.denomination);
+
+    let addresses = match watchlists::addresses(&env, request.addresses, request.watchlist) {
+        Ok(addresses) => addresses.unwrap_or_default(),
+        Err(error) => {
+            env.conclude(&error);
+            return;
+        }
+    };
+
+    let mut activity = positions::read_actions_for(&env, &addresses);
+    let positions = if views::is_fresh(&env, views::POSITIONS) {
+        positions::read(&env, Some(&addresses))
+    } else {
+        positions::positions(&activity)
+    };
+    let exposure = positions::exposure(&positions);
+
+    activity.sort_by_key(|action| std::cmp::Reverse((action.ledger, action.timestamp)));
+    if let Some(limit) = request.limit {
+        activity.truncate(limit);
+    }
+
+    env.conclude(&Portfolio {
+        positions: positions
+            .iter()
+            .map(|position| PositionView::new(position, &denominator))
+            .collect(),
+        exposure: exposure
+            .iter()
+            .map(|exposure| ExposureView::new(exposure, &denominator))
+            .collect(),
+        activity: activity
+            .iter()
+            .map(|action| ActionV1::new(action, &denominator))
+            .collect(),
+    })
+}
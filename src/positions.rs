@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{db, views, Action, Actions};
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("positions")]
+/// Net position of a single account on a single asset, obtained by
+/// summing up all the indexed deltas. Also kept as a materialized view
+/// in the `positions` table.
+pub struct Position {
+    pub source: String,
+    pub asset: String,
+    pub collateral: i128,
+    pub liabilities: i128,
+}
+
+db::row!(
+    Position,
+    "positions",
+    [source, asset, collateral, liabilities]
+);
+
+impl Position {
+    fn new(source: String, asset: String) -> Self {
+        Self {
+            source,
+            asset,
+            collateral: 0,
+            liabilities: 0,
+        }
+    }
+
+    fn apply(&mut self, action: &Actions) {
+        if action.action == Action::Collateral as u32 {
+            self.collateral += action.amount as i128;
+        } else if action.action == Action::Borrow as u32 {
+            self.liabilities += action.amount as i128;
+        }
+    }
+}
+
+/// Position on a single asset combined across a set of accounts.
+#[derive(Serialize, Clone)]
+pub struct Exposure {
+    pub asset: String,
+    pub collateral: i128,
+    pub liabilities: i128,
+    pub net: i128,
+}
+
+/// Reads all the actions performed by the given addresses.
+pub fn read_actions_for(env: &EnvClient, addresses: &[String]) -> Vec<Actions> {
+    let mut addresses = addresses.to_vec();
+    addresses.sort();
+    addresses.dedup();
+
+    let mut actions = Vec::new();
+    for address in addresses {
+        let rows: Vec<Actions> = env
+            .read_filter()
+            .column_equal_to("source", address)
+            .read()
+            .unwrap();
+        actions.extend(rows);
+    }
+
+    actions
+}
+
+/// Aggregates actions into per-account, per-asset positions.
+pub fn positions(actions: &[Actions]) -> Vec<Position> {
+    let mut positions: BTreeMap<(String, String), Position> = BTreeMap::new();
+    for action in actions {
+        positions
+            .entry((action.source.clone(), action.asset.clone()))
+            .or_insert_with(|| Position::new(action.source.clone(), action.asset.clone()))
+            .apply(action);
+    }
+
+    positions.into_values().collect()
+}
+
+/// Reads the positions of the given addresses, or of all accounts, from
+/// the materialized view, computing them from the actions when the view
+/// is stale.
+pub fn read(env: &EnvClient, addresses: Option<&[String]>) -> Vec<Position> {
+    if !views::is_fresh(env, views::POSITIONS) {
+        let actions = match addresses {
+            Some(addresses) => read_actions_for(env, addresses),
+            None => env.read::<Actions>(),
+        };
+        return positions(&actions);
+    }
+
+    let Some(addresses) = addresses else {
+        return env.read::<Position>();
+    };
+    let mut addresses = addresses.to_vec();
+    addresses.sort();
+    addresses.dedup();
+
+    let mut positions = Vec::new();
+    for address in addresses {
+        let rows: Vec<Position> = env
+            .read_filter()
+            .column_equal_to("source", address)
+            .read()
+            .unwrap();
+        positions.extend(rows);
+    }
+
+    positions
+}
+
+impl views::ViewRow for Position {
+    const KEYS: &'static [&'static str] = &["source", "asset"];
+
+    fn merge(&mut self, other: &Self) {
+        self.collateral += other.collateral;
+        self.liabilities += other.liabilities;
+    }
+}
+
+/// Writes the positions resulting from `actions` to the `positions` view,
+/// on top of the existing positions when `incremental`.
+pub fn refresh(env: &EnvClient, actions: &[Actions], incremental: bool) -> Result<(), String> {
+    views::write(env, positions(actions), incremental, |position| {
+        let existing: Vec<Position> = env
+            .read_filter()
+            .column_equal_to("source", position.source.clone())
+            .column_equal_to("asset", position.asset.clone())
+            .read()
+            .unwrap();
+        existing.into_iter().next()
+    })
+}
+
+/// Accounts that performed any of the actions.
+pub fn accounts(actions: &[Actions]) -> BTreeSet<String> {
+    actions.iter().map(|action| action.source.clone()).collect()
+}
+
+/// Combines positions of different accounts into a per-asset exposure.
+pub fn exposure(positions: &[Position]) -> Vec<Exposure> {
+    let mut exposure: BTreeMap<String, Exposure> = BTreeMap::new();
+    for position in positions {
+        let entry = exposure
+            .entry(position.asset.clone())
+            .or_insert_with(|| Exposure {
+                asset: position.asset.clone(),
+                collateral: 0,
+                liabilities: 0,
+                net: 0,
+            });
+        entry.collateral += position.collateral;
+        entry.liabilities += position.liabilities;
+        entry.net = entry.collateral - entry.liabilities;
+    }
+
+    exposure.into_values().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exposure, positions};
+    use crate::{Action, Actions};
+
+    #[test]
+    fn aggregate() {
+        let actions = vec![
+            Actions::test(Action::Collateral, 1000)
+                .with_source("A")
+                .with_asset("XLM"),
+            Actions::test(Action::Collateral, -400)
+                .with_source("A")
+                .with_asset("XLM"),
+            Actions::test(Action::Borrow, 100)
+                .with_source("A")
+                .with_asset("USDC"),
+            Actions::test(Action::Collateral, 500)
+                .with_source("B")
+                .with_asset("XLM"),
+            Actions::test(Action::Borrow, 200)
+                .with_source("B")
+                .with_asset("XLM"),
+        ];
+
+        let positions = positions(&actions);
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0].source, "A");
+        assert_eq!(positions[0].asset, "USDC");
+        assert_eq!(positions[0].liabilities, 100);
+        assert_eq!(positions[1].collateral, 600);
+
+        let exposure = exposure(&positions);
+        assert_eq!(exposure.len(), 2);
+        assert_eq!(exposure[1].asset, "XLM");
+        assert_eq!(exposure[1].collateral, 1100);
+        assert_eq!(exposure[1].liabilities, 200);
+        assert_eq!(exposure[1].net, 900);
+    }
+}
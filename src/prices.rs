@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use zephyr_sdk::{
+    prelude::*,
+    soroban_sdk::xdr::{Hash, LedgerEntryData, ScAddress, ScVal},
+    DatabaseDerive, EnvClient,
+};
+
+use crate::{
+    db,
+    reserves::{self, Reserve},
+};
+
+/// Oracle used by the pool to price its reserves.
+pub const ORACLE: &str = "CALI2BYU2JE6WVRUFYTS6MSBNEHGJ35P4AVCZYF3B6QOE3QKOB2PLE6M";
+
+/// Maximum time, in seconds, between an action and the price it is valued
+/// at, about a day. Older or newer prices don't reflect the action.
+pub const MAX_PRICE_DISTANCE: u64 = 86_400;
+
+/// Decimals of the prices reported by the oracle.
+pub const ORACLE_DECIMALS: u32 = 14;
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("prices")]
+pub struct Prices {
+    pub asset: String,
+    pub price: i128,
+    pub timestamp: u64,
+    pub ledger: u32,
+}
+
+db::row!(Prices, "prices", [asset, price, timestamp, ledger]);
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("latest_prices")]
+/// Most recent price of an asset, kept next to the history so that it can
+/// be read without scanning `prices`.
+pub struct LatestPrices {
+    pub asset: String,
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+db::row!(LatestPrices, "latest_prices", [asset, price, timestamp]);
+
+/// Indexes the prices written by the oracle in the current ledger.
+///
+/// The oracle stores each price as an `i128` under a `u128` key which
+/// holds the price timestamp in the high half and the asset index in
+/// the low half.
+pub fn index(env: &EnvClient) {
+    let oracle = ScAddress::Contract(Hash(
+        stellar_strkey::Contract::from_string(ORACLE).unwrap().0,
+    ));
+    let changes = env.reader().v1_success_ledger_entries();
+
+    let mut indexed = Vec::new();
+    for entry in changes.created.iter().chain(changes.updated.iter()) {
+        let LedgerEntryData::ContractData(data) = &entry.data else {
+            continue;
+        };
+        if data.contract != oracle {
+            continue;
+        }
+        let (ScVal::U128(key), ScVal::I128(price)) = (&data.key, &data.val) else {
+            continue;
+        };
+
+        if let Some(reserve) = reserves::by_oracle_index(key.lo as u32) {
+            indexed.push(Prices {
+                asset: reserve.asset.into(),
+                price: ((price.hi as i128) << 64) | price.lo as i128,
+                timestamp: key.hi,
+                ledger: env.reader().ledger_sequence(),
+            });
+        }
+    }
+    if indexed.is_empty() {
+        return;
+    }
+
+    let mut latest: BTreeMap<String, LatestPrices> = env
+        .read::<LatestPrices>()
+        .into_iter()
+        .map(|latest| (latest.asset.clone(), latest))
+        .collect();
+    for price in indexed {
+        db::put(env, &price);
+
+        let update = LatestPrices {
+            asset: price.asset.clone(),
+            price: price.price,
+            timestamp: price.timestamp,
+        };
+        match latest.get(&price.asset) {
+            None => db::put(env, &update),
+            Some(current) if current.timestamp <= update.timestamp => {
+                db::update(env, &update, &["asset"])
+            }
+            Some(_) => continue,
+        }
+        latest.insert(price.asset, update);
+    }
+}
+
+/// Most recent price of every indexed asset.
+pub fn latest(env: &EnvClient) -> BTreeMap<String, i128> {
+    env.read::<LatestPrices>()
+        .into_iter()
+        .map(|latest| (latest.asset, latest.price))
+        .collect()
+}
+
+/// All the indexed prices of an asset.
+pub fn history(env: &EnvClient, asset: &str) -> Vec<Prices> {
+    env.read_filter()
+        .column_equal_to("asset", asset.to_string())
+        .read()
+        .unwrap()
+}
+
+/// Price closest in time to `timestamp`, `None` if it is further than
+/// `MAX_PRICE_DISTANCE` away.
+pub fn nearest(history: &[Prices], timestamp: u64) -> Option<i128> {
+    history
+        .iter()
+        .min_by_key(|row| row.timestamp.abs_diff(timestamp))
+        .filter(|row| row.timestamp.abs_diff(timestamp) <= MAX_PRICE_DISTANCE)
+        .map(|row| row.price)
+}
+
+/// USD value of an amount of the reserve's token.
+pub fn usd_value(reserve: &Reserve, amount: i128, price: i128) -> f64 {
+    amount as f64 / 10f64.powi(reserve.decimals as i32) * price as f64
+        / 10f64.powi(ORACLE_DECIMALS as i32)
+}
+
+/// Converts a USD value to XLM given the XLM price.
+pub fn xlm_value(usd_value: f64, xlm_price: i128) -> Option<f64> {
+    if xlm_price > 0 {
+        Some(usd_value * 10f64.powi(ORACLE_DECIMALS as i32) / xlm_price as f64)
+    } else {
+        None
+    }
+}
+
+/// USD value of an amount at the latest price, `None` if the asset isn't
+/// configured or was never priced.
+pub fn latest_usd_value(prices: &BTreeMap<String, i128>, asset: &str, amount: i128) -> Option<f64> {
+    let reserve = reserves::get(asset)?;
+    let price = prices.get(asset)?;
+
+    Some(usd_value(reserve, amount, *price))
+}
+
+/// XLM value of a USD value at the latest XLM price.
+pub fn latest_xlm_value(prices: &BTreeMap<String, i128>, usd_value: f64) -> Option<f64> {
+    xlm_value(usd_value, *prices.get(reserves::XLM)?)
+}
+
+/// USD value of an amount at the price closest to `timestamp`, `None` if
+/// the asset isn't configured or wasn't priced around `timestamp`.
+pub fn historical_usd_value(
+    history: &[Prices],
+    asset: &str,
+    amount: i128,
+    timestamp: u64,
+) -> Option<f64> {
+    let reserve = reserves::get(asset)?;
+    let price = nearest(history, timestamp)?;
+
+    Some(usd_value(reserve, amount, price))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{nearest, xlm_value, Prices, MAX_PRICE_DISTANCE, ORACLE_DECIMALS};
+
+    fn price(price: i128, timestamp: u64) -> Prices {
+        Prices {
+            asset: "XLM".into(),
+            price,
+            timestamp,
+            ledger: 0,
+        }
+    }
+
+    #[test]
+    fn nearest_price() {
+        let history = vec![price(10, 1000), price(12, 1300), price(11, 1600)];
+
+        assert_eq!(nearest(&history, 0), Some(10));
+        assert_eq!(nearest(&history, 1400), Some(12));
+        assert_eq!(nearest(&history, 1500), Some(11));
+        assert_eq!(nearest(&[], 1500), None);
+        assert_eq!(nearest(&history, 1600 + MAX_PRICE_DISTANCE), Some(11));
+        assert_eq!(nearest(&history, 1601 + MAX_PRICE_DISTANCE), None);
+    }
+
+    #[test]
+    fn xlm_conversion() {
+        let xlm_price = 10i128.pow(ORACLE_DECIMALS) / 10;
+
+        assert_eq!(xlm_value(25.0, xlm_price), Some(250.0));
+        assert_eq!(xlm_value(0.0, xlm_price), Some(0.0));
+        assert_eq!(xlm_value(25.0, 0), None);
+        assert_eq!(xlm_value(25.0, -1), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    describe, health, params, pool,
+    positions::Position,
+    prices,
+    reserves::{self, RESERVES},
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct RankRequest {
+    address: String,
+}
+
+describe::object! {
+    #[derive(Serialize, Debug, PartialEq)]
+    pub struct Rank {
+        /// USD value the user is ranked on.
+        pub value: f64,
+        /// 1 for the user with the largest value.
+        pub rank: usize,
+        /// Share of users with a value lower than or equal to the user's one.
+        pub percentile: f64,
+        pub users: usize,
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct RankResponse {
+        pub address: String,
+        pub debt: Rank,
+        pub collateral: Rank,
+    }
+}
+
+/// USD value of an account's collateral and liabilities, positions on
+/// unpriced assets are ignored.
+fn account_value(positions: &[Position], prices: &BTreeMap<String, i128>) -> (f64, f64) {
+    let mut collateral = 0.0;
+    let mut liabilities = 0.0;
+    for position in positions {
+        if let (Some(reserve), Some(price)) =
+            (reserves::get(&position.asset), prices.get(&position.asset))
+        {
+            collateral += prices::usd_value(reserve, position.collateral, *price);
+            liabilities += prices::usd_value(reserve, position.liabilities, *price);
+        }
+    }
+
+    (collateral, liabilities)
+}
+
+fn rank(values: &[f64], value: f64) -> Rank {
+    let above = values.iter().filter(|other| **other > value).count();
+    let at_or_below = values.len() - above;
+
+    Rank {
+        value,
+        rank: above + 1,
+        percentile: if values.is_empty() {
+            0.0
+        } else {
+            at_or_below as f64 * 100.0 / values.len() as f64
+        },
+        users: values.len(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn percentile_rank() {
+    let env = EnvClient::empty();
+    let request: RankRequest = params::read_request(&env);
+
+    let prices = prices::latest(&env);
+    let pool_reserves = pool::reserves(&env, RESERVES.iter().map(|reserve| reserve.asset));
+    let accounts = health::by_account(&env, &pool_reserves);
+
+    let mut collateral = Vec::with_capacity(accounts.len());
+    let mut debt = Vec::with_capacity(accounts.len());
+    for positions in accounts.values() {
+        let (account_collateral, account_debt) = account_value(positions, &prices);
+        collateral.push(account_collateral);
+        debt.push(account_debt);
+    }
+
+    let (own_collateral, own_debt) = accounts
+        .get(&request.address)
+        .map_or((0.0, 0.0), |positions| account_value(positions, &prices));
+
+    env.conclude(&RankResponse {
+        address: request.address,
+        debt: rank(&debt, own_debt),
+        collateral: rank(&collateral, own_collateral),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{rank, Rank};
+
+    #[test]
+    fn ranking() {
+        let values = [10.0, 50.0, 20.0, 50.0, 0.0];
+
+        assert_eq!(
+            rank(&values, 50.0),
+            Rank {
+                value: 50.0,
+                rank: 1,
+                percentile: 100.0,
+                users: 5
+            }
+        );
+        assert_eq!(rank(&values, 10.0).rank, 4);
+        assert_eq!(rank(&values, 10.0).percentile, 40.0);
+    }
+}
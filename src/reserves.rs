@@ -0,0 +1,41 @@
+/// Asset of a pool reserve, with its decimals and the position of the
+/// asset in the oracle's asset list. The reserve's factors and rates are
+/// read from the pool.
+pub struct Reserve {
+    pub asset: &'static str,
+    pub decimals: u32,
+    pub oracle_index: u32,
+}
+
+/// Native XLM token, also used to express values in XLM terms.
+pub const XLM: &str = "CAS3J7GYLGXMF6TDJBBYYSE3HQ6BBSMLNUQ34T6TZMYMW2EVH34XOWMA";
+
+pub const RESERVES: &[Reserve] = &[
+    Reserve {
+        asset: XLM,
+        decimals: 7,
+        oracle_index: 0,
+    },
+    // USDC
+    Reserve {
+        asset: "CCW67TSZV3SSS2HXMBQ5JFGCKJNXKZM7UQUWUZPUTHXSTZLEO7SJMI75",
+        decimals: 7,
+        oracle_index: 1,
+    },
+    // EURC
+    Reserve {
+        asset: "CDTKPWPLOURQA2SGTKTUQOWRCBZEORB4BWBOMJ3D3ZTQQSGE5F6JBQLV",
+        decimals: 7,
+        oracle_index: 2,
+    },
+];
+
+pub fn get(asset: &str) -> Option<&'static Reserve> {
+    RESERVES.iter().find(|reserve| reserve.asset == asset)
+}
+
+pub fn by_oracle_index(index: u32) -> Option<&'static Reserve> {
+    RESERVES
+        .iter()
+        .find(|reserve| reserve.oracle_index == index)
+}
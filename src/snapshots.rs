@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{db, pool, prices};
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("snapshots")]
+/// Totals of a pool reserve, read from the reserve state the pool wrote in
+/// the snapshot's ledger.
+pub struct Snapshots {
+    pub asset: String,
+    /// Underlying supplied to the reserve, used as collateral or not.
+    pub collateral: i128,
+    /// Underlying borrowed from the reserve, including accrued interest.
+    pub liabilities: i128,
+    /// Value of the collateral at the latest prices when the snapshot was
+    /// taken, kept for history. The current TVL is valued at the current
+    /// prices instead.
+    pub tvl_usd: Option<f64>,
+    pub tvl_xlm: Option<f64>,
+    pub timestamp: u64,
+    pub ledger: u32,
+}
+
+db::row!(
+    Snapshots,
+    "snapshots",
+    [
+        asset,
+        collateral,
+        liabilities,
+        tvl_usd,
+        tvl_xlm,
+        timestamp,
+        ledger
+    ]
+);
+
+impl Snapshots {
+    /// Values the collateral at the given prices.
+    pub fn value(&mut self, prices: &BTreeMap<String, i128>) {
+        self.tvl_usd = prices::latest_usd_value(prices, &self.asset, self.collateral);
+        self.tvl_xlm = self
+            .tvl_usd
+            .and_then(|tvl_usd| prices::latest_xlm_value(prices, tvl_usd));
+    }
+
+    pub fn utilization(&self) -> f64 {
+        if self.collateral > 0 {
+            self.liabilities as f64 / self.collateral as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Most recent snapshot of the given asset.
+pub fn latest(env: &EnvClient, asset: &str) -> Option<Snapshots> {
+    let snapshots: Vec<Snapshots> = env
+        .read_filter()
+        .column_equal_to("asset", asset.to_string())
+        .read()
+        .unwrap();
+
+    snapshots.into_iter().max_by_key(|snapshot| snapshot.ledger)
+}
+
+/// Writes a new snapshot for every reserve whose state the pool updated
+/// in the current ledger.
+///
+/// Returns the previous and the new snapshot of each updated reserve.
+pub fn update(
+    env: &EnvClient,
+    prices: &BTreeMap<String, i128>,
+) -> Vec<(Option<Snapshots>, Snapshots)> {
+    let mut updated = Vec::new();
+    for (asset, reserve) in pool::reserve_changes(env) {
+        let previous = latest(env, &asset);
+
+        let mut snapshot = Snapshots {
+            asset,
+            collateral: reserve.total_supply(),
+            liabilities: reserve.total_liabilities(),
+            tvl_usd: None,
+            tvl_xlm: None,
+            timestamp: env.reader().ledger_timestamp(),
+            ledger: env.reader().ledger_sequence(),
+        };
+        snapshot.value(prices);
+        db::put(env, &snapshot);
+        updated.push((previous, snapshot));
+    }
+
+    updated
+}
@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{
+    db, describe,
+    metrics::{self, WriteStats},
+    reserves::RESERVES,
+    CONTRACT,
+};
+
+/// Ledgers between two full validations, about a day.
+const REVALIDATION_INTERVAL: u32 = 17_280;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[repr(u32)]
+pub enum Check {
+    /// The contract instance exists on the network.
+    ContractExists,
+    /// The pool emitted events that were indexed.
+    EventsIndexed,
+    /// The oracle wrote prices that were indexed.
+    PricesIndexed,
+}
+
+impl Check {
+    /// Whether the check verifies that the contracts produced indexed data.
+    fn is_activity(kind: u32) -> bool {
+        kind == Check::EventsIndexed as u32 || kind == Check::PricesIndexed as u32
+    }
+}
+
+describe::object! {
+    #[derive(DatabaseDerive, Serialize, Clone)]
+    #[with_name("status")]
+    /// Outcome of a configuration check.
+    pub struct Status {
+        pub kind: u32,
+        pub contract: String,
+        pub ok: bool,
+        pub detail: String,
+    }
+}
+
+db::row!(Status, "status", [kind, contract, ok, detail]);
+
+impl Status {
+    fn new(check: Check, contract: &str, ok: bool, detail: String) -> Self {
+        Self {
+            kind: check as u32,
+            contract: contract.into(),
+            ok,
+            detail,
+        }
+    }
+
+    /// Inserts the check's outcome or replaces the previous one.
+    fn record(&self, env: &EnvClient) {
+        let existing: Vec<Status> = env
+            .read_filter()
+            .column_equal_to("kind", self.kind)
+            .column_equal_to("contract", self.contract.clone())
+            .read()
+            .unwrap();
+
+        if existing.is_empty() {
+            db::put(env, self);
+        } else {
+            db::update(env, self, &["kind", "contract"]);
+        }
+    }
+}
+
+fn configured_contracts() -> Vec<&'static str> {
+    let mut contracts = vec![CONTRACT, crate::prices::ORACLE];
+    contracts.extend(RESERVES.iter().map(|reserve| reserve.asset));
+    contracts
+}
+
+fn contract_exists(env: &EnvClient, contract: &str) -> Status {
+    let exists = stellar_strkey::Contract::from_string(contract)
+        .ok()
+        .and_then(|contract| env.read_contract_instance(contract.0).ok().flatten())
+        .is_some();
+    let detail = if exists {
+        "contract instance found".into()
+    } else {
+        "no contract instance found for the configured id".into()
+    };
+
+    Status::new(Check::ContractExists, contract, exists, detail)
+}
+
+/// Whether `table` holds any row, reading a single column of it.
+fn has_rows(env: &EnvClient, table: &str, column: &str) -> bool {
+    env.db_read(table, &[column], None, None)
+        .is_ok_and(|rows| !rows.rows.is_empty())
+}
+
+/// Checks that `table` holds data indexed from `contract`. The indexed
+/// tables are append-only, so a check that passed isn't read again.
+fn indexed(
+    env: &EnvClient,
+    recorded: &[Status],
+    check: Check,
+    contract: &str,
+    table: &str,
+) -> Status {
+    let passed = recorded
+        .iter()
+        .any(|status| status.kind == check as u32 && status.ok);
+    let indexed = passed || has_rows(env, table, "ledger");
+    let detail = if indexed {
+        format!("{} indexed", table)
+    } else {
+        format!("no {} indexed yet", table)
+    };
+
+    Status::new(check, contract, indexed, detail)
+}
+
+/// Validates the configured contract ids, records the outcome of every
+/// check in the status table and returns it.
+///
+/// When `activity` is set the checks also verify that the configured
+/// contracts produced indexed data, which only makes sense once the
+/// program has been running for a while.
+pub fn validate(env: &EnvClient, activity: bool) -> Vec<Status> {
+    let mut checks: Vec<Status> = configured_contracts()
+        .into_iter()
+        .map(|contract| contract_exists(env, contract))
+        .collect();
+
+    if activity {
+        let recorded = env.read::<Status>();
+        checks.push(indexed(
+            env,
+            &recorded,
+            Check::EventsIndexed,
+            CONTRACT,
+            "events",
+        ));
+        checks.push(indexed(
+            env,
+            &recorded,
+            Check::PricesIndexed,
+            crate::prices::ORACLE,
+            "prices",
+        ));
+    }
+
+    for check in &checks {
+        check.record(env);
+    }
+
+    checks
+}
+
+/// Checks an invocation re-runs given the recorded outcomes: `None` when
+/// the recorded ones still hold, otherwise whether the activity checks are
+/// re-run too.
+fn revalidation(recorded: &[Status], due: bool) -> Option<bool> {
+    let failing = recorded.iter().any(|status| !status.ok);
+    let activity_failing = recorded
+        .iter()
+        .any(|status| !status.ok && Check::is_activity(status.kind));
+
+    if due || recorded.is_empty() || failing {
+        Some(due || activity_failing)
+    } else {
+        None
+    }
+}
+
+/// Checks that the program is correctly configured before indexing.
+///
+/// The contracts are validated on the first invocation and again on every
+/// invocation while any check fails, re-running the activity checks when
+/// one of them is failing. All the checks are re-run every
+/// `REVALIDATION_INTERVAL` ledgers. Every invocation of a misconfigured
+/// deployment logs an error so that it doesn't silently index nothing.
+pub fn ensure_configured(env: &EnvClient) {
+    let recorded = env.read::<Status>();
+    let due = env
+        .reader()
+        .ledger_sequence()
+        .is_multiple_of(REVALIDATION_INTERVAL);
+
+    let checks = match revalidation(&recorded, due) {
+        Some(activity) => {
+            validate(env, activity);
+            env.read::<Status>()
+        }
+        None => recorded,
+    };
+
+    let failing: Vec<String> = checks
+        .into_iter()
+        .filter(|status| !status.ok)
+        .map(|status| format!("{} ({})", status.contract, status.detail))
+        .collect();
+    if !failing.is_empty() {
+        env.log().error(
+            format!(
+                "invalid configuration, failing checks: {}",
+                failing.join(", ")
+            ),
+            None,
+        );
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn validate_config() {
+    let env = EnvClient::empty();
+    let checks = validate(&env, true);
+
+    env.conclude(&checks)
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct StatusResponse {
+        /// Whether the configuration was validated and every check passed.
+        pub ok: bool,
+        pub checks: Vec<Status>,
+        /// Rows written by the recent invocations.
+        pub writes: WriteStats,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn status() {
+    let env = EnvClient::empty();
+    let checks = env.read::<Status>();
+
+    env.conclude(&StatusResponse {
+        ok: !checks.is_empty() && checks.iter().all(|status| status.ok),
+        checks,
+        writes: metrics::recent(&env),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{revalidation, Check, Status};
+
+    fn status(check: Check, ok: bool) -> Status {
+        Status::new(check, "C", ok, String::new())
+    }
+
+    #[test]
+    fn failing_checks_are_rerun() {
+        assert_eq!(revalidation(&[], false), Some(false));
+        assert_eq!(
+            revalidation(&[status(Check::ContractExists, true)], false),
+            None
+        );
+        assert_eq!(
+            revalidation(&[status(Check::ContractExists, false)], false),
+            Some(false)
+        );
+        assert_eq!(
+            revalidation(
+                &[
+                    status(Check::ContractExists, true),
+                    status(Check::EventsIndexed, false)
+                ],
+                false
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            revalidation(&[status(Check::ContractExists, true)], true),
+            Some(true)
+        );
+    }
+}
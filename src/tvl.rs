@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::EnvClient;
+
+use crate::{
+    denomination::{Amount, Denomination, Denominator},
+    describe, params, prices,
+    snapshots::Snapshots,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct TvlRequest {
+    asset: Option<String>,
+    /// Also return the snapshots taken since this ledger.
+    from_ledger: Option<u32>,
+    /// Denomination of the collateral and liabilities amounts.
+    denomination: Option<Denomination>,
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct AssetTvl {
+        pub asset: String,
+        pub collateral: Amount,
+        pub liabilities: Amount,
+        pub tvl_usd: Option<f64>,
+        pub tvl_xlm: Option<f64>,
+        pub timestamp: u64,
+        pub ledger: u32,
+    }
+}
+
+impl AssetTvl {
+    fn new(snapshot: &Snapshots, denominator: &Denominator) -> Self {
+        Self {
+            asset: snapshot.asset.clone(),
+            collateral: denominator.amount(&snapshot.asset, snapshot.collateral),
+            liabilities: denominator.amount(&snapshot.asset, snapshot.liabilities),
+            tvl_usd: snapshot.tvl_usd,
+            tvl_xlm: snapshot.tvl_xlm,
+            timestamp: snapshot.timestamp,
+            ledger: snapshot.ledger,
+        }
+    }
+}
+
+describe::object! {
+    #[derive(Serialize)]
+    pub struct Tvl {
+        /// Latest amounts of each asset, valued at the current prices.
+        pub assets: Vec<AssetTvl>,
+        /// Sum over the assets that have a price.
+        pub total_usd: f64,
+        pub total_xlm: f64,
+        /// Snapshots valued at the prices of their time.
+        pub history: Vec<AssetTvl>,
+    }
+}
+
+/// Latest snapshot of each asset, valued at the given prices.
+fn current(snapshots: &[Snapshots], prices: &BTreeMap<String, i128>) -> Vec<Snapshots> {
+    let mut latest: BTreeMap<&str, &Snapshots> = BTreeMap::new();
+    for snapshot in snapshots {
+        let entry = latest.entry(snapshot.asset.as_str()).or_insert(snapshot);
+        if snapshot.ledger > entry.ledger {
+            *entry = snapshot;
+        }
+    }
+
+    latest
+        .into_values()
+        .map(|snapshot| {
+            let mut snapshot = snapshot.clone();
+            snapshot.value(prices);
+            snapshot
+        })
+        .collect()
+}
+
+#[no_mangle]
+pub extern "C" fn tvl() {
+    let env = EnvClient::empty();
+    let request: TvlRequest = params::read_request(&env);
+    let denominator = Denominator::new(&env, request.denomination);
+
+    let snapshots: Vec<Snapshots> = if let Some(asset) = request.asset {
+        env.read_filter()
+            .column_equal_to("asset", asset)
+            .read()
+            .unwrap()
+    } else {
+        env.read()
+    };
+
+    let current = current(&snapshots, &prices::latest(&env));
+
+    let mut history: Vec<&Snapshots> = match request.from_ledger {
+        Some(from) => snapshots
+            .iter()
+            .filter(|snapshot| snapshot.ledger >= from)
+            .collect(),
+        None => Vec::new(),
+    };
+    history.sort_by_key(|snapshot| snapshot.ledger);
+
+    env.conclude(&Tvl {
+        total_usd: current.iter().filter_map(|snapshot| snapshot.tvl_usd).sum(),
+        total_xlm: current.iter().filter_map(|snapshot| snapshot.tvl_xlm).sum(),
+        assets: current
+            .iter()
+            .map(|snapshot| AssetTvl::new(snapshot, &denominator))
+            .collect(),
+        history: history
+            .into_iter()
+            .map(|snapshot| AssetTvl::new(snapshot, &denominator))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::current;
+    use crate::{
+        prices::ORACLE_DECIMALS,
+        reserves::{RESERVES, XLM},
+        snapshots::Snapshots,
+    };
+
+    fn snapshot(asset: &str, collateral: i128, tvl_usd: f64, ledger: u32) -> Snapshots {
+        Snapshots {
+            asset: asset.into(),
+            collateral,
+            liabilities: 0,
+            tvl_usd: Some(tvl_usd),
+            tvl_xlm: None,
+            timestamp: 0,
+            ledger,
+        }
+    }
+
+    #[test]
+    fn current_tvl() {
+        let usdc = RESERVES
+            .iter()
+            .find(|reserve| reserve.asset != XLM)
+            .unwrap();
+        let xlm = RESERVES
+            .iter()
+            .find(|reserve| reserve.asset == XLM)
+            .unwrap();
+        let prices = BTreeMap::from([
+            (XLM.to_string(), 10i128.pow(ORACLE_DECIMALS) / 10),
+            (usdc.asset.to_string(), 10i128.pow(ORACLE_DECIMALS)),
+        ]);
+
+        let snapshots = vec![
+            snapshot(XLM, 1_000 * 10i128.pow(xlm.decimals), 200.0, 1),
+            snapshot(XLM, 2_000 * 10i128.pow(xlm.decimals), 150.0, 5),
+            snapshot(usdc.asset, 50 * 10i128.pow(usdc.decimals), 50.0, 3),
+            snapshot("unpriced", 1_000, 10.0, 4),
+        ];
+
+        let current = current(&snapshots, &prices);
+        assert_eq!(current.len(), 3);
+        let total_usd: f64 = current.iter().filter_map(|snapshot| snapshot.tvl_usd).sum();
+        let total_xlm: f64 = current.iter().filter_map(|snapshot| snapshot.tvl_xlm).sum();
+        // Latest XLM amount at the current price, not the stored values.
+        assert_eq!(total_usd, 250.0);
+        assert_eq!(total_xlm, 2_500.0);
+        assert!(current
+            .iter()
+            .any(|snapshot| snapshot.asset == "unpriced" && snapshot.tvl_usd.is_none()));
+    }
+}
@@ -0,0 +1,260 @@
+use serde::Serialize;
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{
+    db::{self, FailureState, WriteFailures},
+    flows, positions, Actions,
+};
+
+pub const POSITIONS: &str = "positions";
+pub const DAILY_FLOWS: &str = "daily_flows";
+
+const VIEWS: &[&str] = &[POSITIONS, DAILY_FLOWS];
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("view_state")]
+/// Refresh state of a materialized view.
+pub struct ViewState {
+    pub view: String,
+    /// Whether the view reflects all the indexed actions.
+    pub fresh: bool,
+    pub ledger: u32,
+}
+
+db::row!(ViewState, "view_state", [view, fresh, ledger]);
+
+/// Row of a materialized view, holding totals over the actions that share
+/// its `KEYS` columns.
+pub trait ViewRow: db::Row {
+    const KEYS: &'static [&'static str];
+
+    /// Adds the totals of `other` to the row.
+    fn merge(&mut self, other: &Self);
+}
+
+/// How a view is refreshed on a ledger.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Refresh {
+    /// The ledger's actions are added to the view.
+    Incremental,
+    /// The view is recomputed from all the indexed actions.
+    Rebuild,
+    /// The view is marked stale, some actions are pending in
+    /// `write_failures` and both the ledger's actions and a rebuild would
+    /// miss them.
+    Deferred,
+}
+
+fn plan(state: Option<&ViewState>, actions_pending: bool) -> Refresh {
+    match state {
+        _ if actions_pending => Refresh::Deferred,
+        Some(state) if state.fresh => Refresh::Incremental,
+        _ => Refresh::Rebuild,
+    }
+}
+
+fn state(env: &EnvClient, view: &str) -> Option<ViewState> {
+    let states: Vec<ViewState> = env
+        .read_filter()
+        .column_equal_to("view", view.to_string())
+        .read()
+        .unwrap();
+
+    states.into_iter().next()
+}
+
+/// Whether the view can be read instead of computing the result live.
+pub fn is_fresh(env: &EnvClient, view: &str) -> bool {
+    state(env, view).is_some_and(|state| state.fresh)
+}
+
+/// Writes `rows` to a view, adding them to the existing rows when
+/// `incremental` and replacing the existing rows otherwise.
+///
+/// Stops at the first failed write, leaving the view partially written.
+fn write_rows<T: ViewRow>(
+    rows: Vec<T>,
+    incremental: bool,
+    existing: impl Fn(&T) -> Option<T>,
+    mut write: impl FnMut(&T, bool) -> Result<(), String>,
+) -> Result<(), String> {
+    for mut row in rows {
+        let found = existing(&row);
+        if let (true, Some(found)) = (incremental, &found) {
+            row.merge(found);
+        }
+        write(&row, found.is_some())?;
+    }
+
+    Ok(())
+}
+
+/// Writes `rows` to their view. Failures aren't captured in
+/// `write_failures`: a retried write would be applied on top of later
+/// totals, so the view is marked stale and rebuilt instead.
+pub fn write<T: ViewRow>(
+    env: &EnvClient,
+    rows: Vec<T>,
+    incremental: bool,
+    existing: impl Fn(&T) -> Option<T>,
+) -> Result<(), String> {
+    write_rows(rows, incremental, existing, |row, exists| {
+        if exists {
+            db::try_update(env, row, T::KEYS)
+        } else {
+            db::try_put(env, row)
+        }
+    })
+}
+
+fn apply(
+    env: &EnvClient,
+    view: &str,
+    actions: &[Actions],
+    incremental: bool,
+) -> Result<(), String> {
+    match view {
+        POSITIONS => positions::refresh(env, actions, incremental),
+        DAILY_FLOWS => flows::refresh(env, actions, incremental),
+        _ => unreachable!(),
+    }
+}
+
+/// Refreshes the views with the ledger's actions.
+///
+/// A view whose refresh fails, or that would miss an action write captured
+/// in `write_failures`, is marked stale. Stale views and views without a
+/// recorded state are rebuilt from all the indexed actions once no action
+/// write is pending.
+pub fn refresh(env: &EnvClient, actions: &[Actions]) {
+    let failures: Vec<WriteFailures> = env
+        .read_filter()
+        .column_equal_to("state", FailureState::Pending as u32)
+        .read()
+        .unwrap();
+    let actions_pending = failures
+        .iter()
+        .any(|failure| failure.target_table == "actions");
+
+    for view in VIEWS {
+        let state = state(env, view);
+        let result = match plan(state.as_ref(), actions_pending) {
+            Refresh::Incremental => apply(env, view, actions, true),
+            Refresh::Rebuild => apply(env, view, &env.read::<Actions>(), false),
+            Refresh::Deferred => Err("actions are pending in write_failures".into()),
+        };
+        if let Err(error) = &result {
+            env.log()
+                .error(format!("view {} is stale: {}", view, error), None);
+        }
+
+        let updated = ViewState {
+            view: view.to_string(),
+            fresh: result.is_ok(),
+            ledger: env.reader().ledger_sequence(),
+        };
+        let recorded = match state {
+            Some(_) => db::try_update(env, &updated, &["view"]),
+            None => db::try_put(env, &updated),
+        };
+        if let Err(error) = recorded {
+            env.log().error(
+                format!("couldn't record the state of view {}: {}", view, error),
+                None,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{plan, write_rows, Refresh, ViewState};
+    use crate::positions::Position;
+
+    fn position(source: &str, collateral: i128) -> Position {
+        Position {
+            source: source.into(),
+            asset: "XLM".into(),
+            collateral,
+            liabilities: 0,
+        }
+    }
+
+    fn state(fresh: bool) -> ViewState {
+        ViewState {
+            view: "positions".into(),
+            fresh,
+            ledger: 0,
+        }
+    }
+
+    type Table = BTreeMap<String, Position>;
+
+    fn write(
+        table: &mut Table,
+        rows: Vec<Position>,
+        incremental: bool,
+        fail_after: usize,
+    ) -> Result<(), String> {
+        let snapshot = table.clone();
+        let mut written = 0;
+        write_rows(
+            rows,
+            incremental,
+            |row| snapshot.get(&row.source).cloned(),
+            |row, _| {
+                if written == fail_after {
+                    return Err("write failed".into());
+                }
+                written += 1;
+                table.insert(row.source.clone(), row.clone());
+                Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn failed_write_is_rebuilt_on_next_ledger() {
+        let mut table = Table::new();
+
+        // First ledger, A and B supply.
+        assert_eq!(plan(None, false), Refresh::Rebuild);
+        assert_eq!(plan(Some(&state(true)), true), Refresh::Deferred);
+        write(
+            &mut table,
+            vec![position("A", 100), position("B", 50)],
+            false,
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(plan(Some(&state(true)), false), Refresh::Incremental);
+
+        // Second ledger, only A's delta is written before a failure.
+        let result = write(
+            &mut table,
+            vec![position("A", 10), position("B", 5)],
+            true,
+            1,
+        );
+        assert!(result.is_err());
+        assert_eq!(table["A"].collateral, 110);
+        assert_eq!(table["B"].collateral, 50);
+
+        // Next ledger, the stale view is rebuilt from all the actions
+        // without counting A's delta twice.
+        let stale = state(result.is_ok());
+        assert_eq!(plan(Some(&stale), true), Refresh::Deferred);
+        assert_eq!(plan(Some(&stale), false), Refresh::Rebuild);
+        write(
+            &mut table,
+            vec![position("A", 110), position("B", 55)],
+            false,
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(table["A"].collateral, 110);
+        assert_eq!(table["B"].collateral, 55);
+    }
+}
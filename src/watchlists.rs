@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use zephyr_sdk::{prelude::*, DatabaseDerive, EnvClient};
+
+use crate::{api::ApiError, db, describe, params};
+
+#[derive(DatabaseDerive, Serialize, Clone)]
+#[with_name("watchlists")]
+/// Named cohort of addresses tracked by a monitoring team.
+pub struct Watchlists {
+    pub id: u32,
+    pub name: String,
+    /// Bincode-encoded `Vec<String>` of addresses.
+    pub addresses: Vec<u8>,
+    pub deleted: bool,
+}
+
+db::row!(Watchlists, "watchlists", [id, name, addresses, deleted]);
+
+describe::object! {
+    /// Watchlist as returned by the endpoints, with its addresses decoded.
+    #[derive(Serialize, Clone, Debug, PartialEq)]
+    pub struct Watchlist {
+        pub id: u32,
+        pub name: String,
+        pub addresses: Vec<String>,
+    }
+}
+
+impl From<Watchlists> for Watchlist {
+    fn from(row: Watchlists) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            addresses: bincode::deserialize(&row.addresses).unwrap_or_default(),
+        }
+    }
+}
+
+impl Watchlist {
+    fn row(&self, deleted: bool) -> Watchlists {
+        Watchlists {
+            id: self.id,
+            name: self.name.clone(),
+            addresses: bincode::serialize(&self.addresses).unwrap(),
+            deleted,
+        }
+    }
+}
+
+pub fn get(env: &EnvClient, id: u32) -> Option<Watchlist> {
+    let watchlists: Vec<Watchlists> = env.read_filter().column_equal_to("id", id).read().unwrap();
+
+    watchlists
+        .into_iter()
+        .find(|watchlist| !watchlist.deleted)
+        .map(Watchlist::from)
+}
+
+/// Addresses a query is scoped to: the explicit ones plus those of the
+/// watchlist, if any. `None` when the query isn't scoped to addresses.
+///
+/// Fails when the watchlist doesn't exist or was deleted.
+pub fn addresses(
+    env: &EnvClient,
+    explicit: Vec<String>,
+    watchlist: Option<u32>,
+) -> Result<Option<Vec<String>>, ApiError> {
+    if explicit.is_empty() && watchlist.is_none() {
+        return Ok(None);
+    }
+
+    let mut addresses = explicit;
+    if let Some(id) = watchlist {
+        let watchlist = get(env, id).ok_or_else(|| ApiError::unknown_watchlist(id))?;
+        addresses.extend(watchlist.addresses);
+    }
+    normalize(&mut addresses);
+
+    Ok(Some(addresses))
+}
+
+fn normalize(addresses: &mut Vec<String>) {
+    addresses.sort();
+    addresses.dedup();
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateWatchlistRequest {
+    name: String,
+    #[serde(default)]
+    addresses: Vec<String>,
+}
+
+#[no_mangle]
+pub extern "C" fn create_watchlist() {
+    let env = EnvClient::empty();
+    let request: CreateWatchlistRequest = params::read_request(&env);
+
+    let id = env
+        .read::<Watchlists>()
+        .iter()
+        .map(|watchlist| watchlist.id + 1)
+        .max()
+        .unwrap_or(0);
+    let mut watchlist = Watchlist {
+        id,
+        name: request.name,
+        addresses: request.addresses,
+    };
+    normalize(&mut watchlist.addresses);
+    match db::try_put(&env, &watchlist.row(false)) {
+        Ok(()) => env.conclude(&watchlist),
+        Err(error) => env.conclude(&ApiError { error }),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateWatchlistRequest {
+    id: u32,
+    name: Option<String>,
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    delete: bool,
+}
+
+#[no_mangle]
+pub extern "C" fn update_watchlist() {
+    let env = EnvClient::empty();
+    let request: UpdateWatchlistRequest = params::read_request(&env);
+
+    let Some(mut watchlist) = get(&env, request.id) else {
+        env.conclude(ApiError::unknown_watchlist(request.id));
+        return;
+    };
+
+    if let Some(name) = request.name {
+        watchlist.name = name;
+    }
+    watchlist.addresses.extend(request.add);
+    watchlist
+        .addresses
+        .retain(|address| !request.remove.contains(address));
+    normalize(&mut watchlist.addresses);
+    match db::try_update(&env, &watchlist.row(request.delete), &["id"]) {
+        Ok(()) => env.conclude(&watchlist),
+        Err(error) => env.conclude(&ApiError { error }),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WatchlistsRequest {
+    /// Return a single watchlist instead of all of them.
+    id: Option<u32>,
+}
+
+#[no_mangle]
+pub extern "C" fn watchlists() {
+    let env = EnvClient::empty();
+    let request: WatchlistsRequest = params::read_request(&env);
+
+    let watchlists: Vec<Watchlist> = match request.id {
+        Some(id) => match get(&env, id) {
+            Some(watchlist) => vec![watchlist],
+            None => {
+                env.conclude(ApiError::unknown_watchlist(id));
+                return;
+            }
+        },
+        None => env
+            .read::<Watchlists>()
+            .into_iter()
+            .filter(|watchlist| !watchlist.deleted)
+            .map(Watchlist::from)
+            .collect(),
+    };
+
+    env.conclude(&watchlists)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Watchlist;
+
+    #[test]
+    fn addresses_round_trip() {
+        let watchlist = Watchlist {
+            id: 3,
+            name: "whales".into(),
+            addresses: vec!["A".into(), "B".into()],
+        };
+
+        let row = watchlist.row(false);
+        assert!(!row.deleted);
+        assert_eq!(Watchlist::from(row), watchlist);
+    }
+}